@@ -1,14 +1,20 @@
+mod ast;
+mod checker;
+pub mod diagnostic;
 mod lexer;
+mod loader;
 mod parser;
-mod ast;
 pub mod token;
 pub mod token_stream;
-pub mod utils;
 
+use std::env;
+use std::process;
+
+use checker::Checker;
+use diagnostic::Diagnostic;
 use lexer::Lexer;
-use token_stream::TokenStream;
+use loader::Loader;
 
-use parser::decl_parser::DeclParser;
 use crate::utils::exit_with_err_msg;
 
 pub struct Compiler {
@@ -21,23 +27,37 @@ impl Compiler {
     pub fn new(file_path: String, file_source: String, phase: Phase) -> Self {
         println!("Compiling...\n");
 
-        Compiler {
-            file_path: file_path,
-            file_source: file_source,
-            phase: phase,
-        }
+        Compiler { file_path, file_source, phase }
     }
 
+    /// Imports that aren't found next to the importing file are looked up in the directories listed in `MOVA_PATH`.
     pub fn compile(&self) {
-        let tokens = Lexer::new(&self.file_path, &self.file_source).tokenize();
-
-        let mut stream = if self.phase == Phase::Parser || self.phase == Phase::All {
-            TokenStream::new(tokens)
-        } else {
+        if self.phase == Phase::Lexer {
+            Lexer::new(&self.file_path, &self.file_source).tokenize();
             exit_with_err_msg("")
-        };
+        }
+
+        let search_paths = env::var_os("MOVA_PATH").map(|paths| env::split_paths(&paths).collect()).unwrap_or_default();
+        let (modules, diagnostics) = Loader::new(search_paths).load(&self.file_path, &self.file_source);
+        self.report(diagnostics);
+
+        if self.phase != Phase::All {
+            return;
+        }
+
+        self.report(Checker::new().check(&modules));
+    }
+
+    fn report(&self, diagnostics: Vec<Diagnostic>) {
+        if diagnostics.is_empty() {
+            return;
+        }
+
+        for diagnostic in &diagnostics {
+            diagnostic.print(&self.file_path);
+        }
 
-        let decls = DeclParser::new(&mut stream).parse();
+        process::exit(1);
     }
 }
 
@@ -1,31 +1,231 @@
-use super::expr::Expr;
+use std::fmt;
+
+use super::expr::{Expr, Path};
 use super::stmt::Stmt;
+use super::type_info::TypeInfo;
+use crate::compiler::diagnostic::Span;
 
+#[derive(Debug)]
 pub struct VarDecl {
-    pub name: String,
-    pub expr: Expr,
+    pub target: BindingTarget,
+    pub type_info: Option<TypeInfo>,
+    pub expr: Option<Expr>,
+    /// Only globals can be internal.
+    pub is_internal: bool,
+    pub span: Span,
 }
 
+#[derive(Debug)]
 pub struct LetDecl {
+    pub target: BindingTarget,
+    pub type_info: Option<TypeInfo>,
+    pub expr: Option<Expr>,
+    /// Only globals can be internal.
+    pub is_internal: bool,
+    pub span: Span,
+}
+
+/// What `var` and `let` bind: a name, or one name per element of a tuple with `let (q, r) := divmod(a, b);`. A `_`
+/// element is skipped.
+#[derive(Debug)]
+pub enum BindingTarget {
+    Name(String),
+    Tuple(Vec<String>),
+}
+
+impl fmt::Display for BindingTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingTarget::Name(name) => write!(f, "{name}"),
+            BindingTarget::Tuple(names) => write!(f, "({})", names.join(", ")),
+        }
+    }
+}
+
+/// `const NAME: type = expr;`, a global whose value is computed at compile time. The expression can only use
+/// literals, operators, other consts and calls to const functions. Uses are folded to the value, so a const takes no
+/// storage and can appear wherever a constant is required, such as array lengths and switch cases.
+#[derive(Debug)]
+pub struct ConstDecl {
     pub name: String,
+    pub type_info: TypeInfo,
     pub expr: Expr,
+    pub is_internal: bool,
+    pub span: Span,
 }
 
+/// `#[name]` or `#[name(args)]`, written before a function, struct, field or statement.
+#[derive(Debug)]
+pub struct Attribute {
+    pub name: String,
+    pub args: Vec<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
 pub struct FnDecl {
+    pub attrs: Vec<Attribute>,
     pub name: String,
+    pub type_params: Vec<TypeParam>,
     pub params: Vec<FnParam>,
+    /// `None` when the function is declared without `->` and returns nothing.
+    pub return_type: Option<TypeInfo>,
     pub stmts: Vec<Stmt>,
+    pub is_internal: bool,
+    /// `const fn`, which can be called in const expressions. Its body is limited to `let` bindings, `if` and `return`.
+    pub is_const: bool,
+    pub span: Span,
 }
 
+#[derive(Debug)]
 pub struct FnParam {
-    // TODO add type, currently by default it's int
-    // type_info: TypeInfo
     pub name: String,
+    /// `None` when the parameter is declared without a type, in which case it's int.
+    pub type_info: Option<TypeInfo>,
     pub is_mutable: bool,
+    /// `let port: int = 80`, the value of the argument when a call leaves it out. It's evaluated at each such call and
+    /// can't refer to other parameters. Parameters with a default come after those without.
+    pub default: Option<Expr>,
+    /// `let args: ...T`, which takes any number of trailing arguments, seen as a `[T]` slice in the body. Only the
+    /// last parameter can be variadic.
+    pub is_variadic: bool,
+    pub span: Span,
+}
+
+impl FnParam {
+    /// Whether this is the receiver of a method, written `self` or `var self`.
+    pub fn is_self(&self) -> bool {
+        self.name == "self"
+    }
+}
+
+/// `T` in `fn max<T>(...)` or `struct Pair<A, B>`.
+///
+/// Generic items are compiled by monomorphization: every distinct list of type arguments an item is used with gets its
+/// own copy, specialized as if it had been written for those types. Bodies are checked once, with each type parameter
+/// as an opaque type that only supports what every type does, so no instantiation can fail to compile.
+#[derive(Debug)]
+pub struct TypeParam {
+    pub name: String,
+    /// `T: Display + Eq`, interfaces the type arguments have to implement, whose methods the body can then call on
+    /// values of the type parameter. Only type parameters of functions can have bounds.
+    pub bounds: Vec<Path>,
+    pub span: Span,
+}
+
+/// `impl Point { init(...) { ... } fn len(self) -> float { ... } }`. The constructor is stored as a function named
+/// `init`, which can't clash with a method since `init` is a keyword.
+///
+/// The impl of a generic struct names its type parameters, `impl Pair<A, B> { ... }`, and they're in scope in every
+/// function of the block.
+#[derive(Debug)]
+pub struct ImplDecl {
+    /// `impl Display for Point { ... }`, which implements the interface. Its functions are methods of the type like
+    /// any other.
+    pub interface: Option<Path>,
+    pub type_name: String,
+    pub type_params: Vec<TypeParam>,
+    pub fns: Vec<FnDecl>,
+    pub span: Span,
+}
+
+/// `interface Display { fn show(self) -> string; }`, methods a type provides by implementing the interface. `Self` in
+/// a signature is the implementing type.
+///
+/// Calls through an interface are dispatched statically: a generic function bounded by it is monomorphized like any
+/// other, each copy calling the methods of its type argument directly.
+#[derive(Debug)]
+pub struct InterfaceDecl {
+    pub name: String,
+    /// Signatures, stored as functions without a body.
+    pub methods: Vec<FnDecl>,
+    pub is_internal: bool,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct StructDecl {
+    pub attrs: Vec<Attribute>,
+    pub name: String,
+    pub type_params: Vec<TypeParam>,
+    pub fields: Vec<StructField>,
+    pub is_internal: bool,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct StructField {
+    pub attrs: Vec<Attribute>,
+    pub name: String,
+    pub type_info: TypeInfo,
+    /// Used when a struct literal doesn't specify the field.
+    pub default: Option<Expr>,
+    pub is_internal: bool,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct EnumDecl {
+    pub name: String,
+    pub variants: Vec<EnumVariant>,
+    pub is_internal: bool,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct EnumVariant {
+    pub name: String,
+    pub payload: VariantPayload,
+    /// Explicit `= value`, only allowed when no variant of the enum has a payload.
+    pub discriminant: Option<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum VariantPayload {
+    Unit,
+    /// `Some(int)`.
+    Tuple(Vec<TypeInfo>),
+    /// `Move { x: int, y: int }`.
+    Struct(Vec<StructField>),
+}
+
+/// `import "path/to/mod";` or `import math::vec;`. The module is referred to by the last segment of the path.
+#[derive(Debug)]
+pub struct ImportDecl {
+    pub path: ImportPath,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum ImportPath {
+    /// File path, relative to the importing file or a search path, with an optional `.mv` extension.
+    File(String),
+    /// `math::vec` for `math/vec.mv`.
+    Module(Vec<String>),
+}
+
+impl fmt::Display for ImportPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportPath::File(path) => write!(f, "{path}"),
+            ImportPath::Module(segments) => write!(f, "{}", segments.join("::")),
+        }
+    }
 }
 
+/// Items are visible to importing modules unless they're declared `internal`, which limits them to their own module.
+#[derive(Debug)]
 pub enum Decl {
     Var(VarDecl),
     Let(LetDecl),
+    Const(ConstDecl),
     Fn(FnDecl),
+    Struct(StructDecl),
+    Enum(EnumDecl),
+    Impl(ImplDecl),
+    Interface(InterfaceDecl),
+    Import(ImportDecl),
+    /// Placeholder for a declaration that failed to parse.
+    Error,
 }
@@ -1 +1,155 @@
-pub struct Expr {}
+use std::fmt;
+
+use super::decl::FnParam;
+use super::stmt::{Block, LoopStmt};
+use super::type_info::TypeInfo;
+use crate::compiler::diagnostic::Span;
+
+#[derive(Debug)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum ExprKind {
+    Int(i32),
+    Float(f32),
+    Str(String),
+    Bool(bool),
+    /// No value, for any optional type.
+    Nil,
+    Ident(String),
+    /// `Color::Red`, a name with at least two segments.
+    Path(Path),
+    /// Generic function with explicit type arguments, `max::<int>`.
+    Generic(Path, Vec<TypeInfo>),
+    Unary(UnaryOp, Box<Expr>),
+    Update(UpdateOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// `place = value`, or `place += value` with the operator of a compound assignment. Evaluates to the value stored.
+    Assign(Option<BinaryOp>, Box<Expr>, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(Box<Expr>, Vec<Arg>),
+    /// `a.b`, or `t.0` for an element of a tuple.
+    Field(Box<Expr>, String),
+    /// `a?.b`, the field of an optional or `nil` if there's no value. A call `a?.f()` only runs the method if there's
+    /// a value.
+    OptionalField(Box<Expr>, String),
+    /// `a ?? b`, the value of an optional or `b` if it's `nil`, which is only evaluated in that case.
+    Coalesce(Box<Expr>, Box<Expr>),
+    /// `[1, 2, 3]`.
+    Array(Vec<Expr>),
+    /// `(1, "a")`, with at least two elements.
+    Tuple(Vec<Expr>),
+    /// `[value; count]`, an array with `count` copies of `value`.
+    ArrayRepeat(Box<Expr>, Box<Expr>),
+    /// `a[i]`.
+    Index(Box<Expr>, Box<Expr>),
+    /// `a[start..end]` or `a[start..=end]`, a slice of an array or of another slice.
+    Slice(Box<Expr>, Range),
+    /// `Point { x: 1, y: 2 }` or `Msg::Move { x: 1, y: 2 }`.
+    StructLit(Path, Vec<FieldInit>),
+    Closure(Box<Closure>),
+    /// `loop { ... }` in an expression, evaluating to the value its `break` leaves it with.
+    Loop(Box<LoopStmt>),
+}
+
+/// Argument of a call, `value`, or `name: value` to pass it for the parameter with that name. Named arguments come
+/// after positional ones and can only be used when calling a function, a method or a constructor by name.
+#[derive(Debug)]
+pub struct Arg {
+    pub name: Option<String>,
+    pub value: Expr,
+    /// `...values`, passing the elements of a slice as the arguments of a variadic parameter.
+    pub spread: bool,
+    pub span: Span,
+}
+
+/// `|let x, var acc| x + acc`, or `|let x: int| -> int { ... }` with a block body. The type of a closure with an
+/// expression body is the type of the expression, a block body returns nothing unless a return type is declared.
+///
+/// Closures capture the local variables they use: `let` bindings by value, copied when the closure is created, and
+/// `var` bindings by reference, so the closure and the enclosing function see each other's changes. Globals aren't
+/// captured, they're accessed directly. A `var` captured by reference lives as long as the closures that capture it,
+/// so a closure can be returned or stored past the scope of the variable, which is deliberately left unchecked.
+#[derive(Debug)]
+pub struct Closure {
+    pub params: Vec<FnParam>,
+    pub return_type: Option<TypeInfo>,
+    pub body: ClosureBody,
+}
+
+#[derive(Debug)]
+pub enum ClosureBody {
+    Expr(Expr),
+    Block(Block),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub segments: Vec<String>,
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.segments.join("::"))
+    }
+}
+
+#[derive(Debug)]
+pub struct FieldInit {
+    pub name: String,
+    pub expr: Expr,
+    pub span: Span,
+}
+
+/// `start..end` or `start..=end`.
+#[derive(Debug)]
+pub struct Range {
+    pub start: Box<Expr>,
+    pub end: Box<Expr>,
+    pub inclusive: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+    /// `@x`, the address of a variable, a field or a dereferenced pointer.
+    AddrOf,
+    /// `p^`, written after the operand.
+    Deref,
+}
+
+/// `++x` and `--x` evaluate to the value after the update, `x++` and `x--` to the value before it. The operand is a
+/// writable int or float place.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateOp {
+    PreInc,
+    PreDec,
+    PostInc,
+    PostDec,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    And,
+    Or,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Expr { kind, span }
+    }
+}
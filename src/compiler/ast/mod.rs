@@ -1,3 +1,5 @@
 pub mod decl;
 pub mod expr;
-pub mod stmt;
\ No newline at end of file
+pub mod pattern;
+pub mod stmt;
+pub mod type_info;
@@ -0,0 +1,33 @@
+use super::expr::{Expr, Path, Range};
+use crate::compiler::diagnostic::Span;
+
+/// What a switch arm matches the subject against.
+#[derive(Debug)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum PatternKind {
+    /// `_`, which matches anything.
+    Wildcard,
+    /// `name`, a const if there's one with that name, otherwise a binding that matches anything.
+    Ident(String),
+    /// A constant expression, `1`, `"a"` or `SIZE * 2`.
+    Expr(Expr),
+    /// `1..5` or `1..=5`.
+    Range(Range),
+    /// `Shape::Circle(r)`, or `Color::Red` and `geo::ORIGIN` without arguments, either a unit variant or a const.
+    Variant(Path, Option<Vec<Pattern>>),
+    /// `Point { x: 0, y }` or `Msg::Move { x, .. }`, where `..` allows leaving out fields.
+    Struct(Path, Vec<FieldPattern>, bool),
+}
+
+/// `x: pattern`, or `x` binding the field to a variable of the same name.
+#[derive(Debug)]
+pub struct FieldPattern {
+    pub name: String,
+    pub pattern: Pattern,
+    pub span: Span,
+}
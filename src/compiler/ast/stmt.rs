@@ -1 +1,155 @@
-pub struct Stmt{}
\ No newline at end of file
+use super::decl::{Attribute, LetDecl, VarDecl};
+use super::expr::{Expr, Range};
+use super::pattern::Pattern;
+use crate::compiler::diagnostic::Span;
+
+#[derive(Debug)]
+pub struct Block {
+    pub stmts: Vec<Stmt>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct ReturnStmt {
+    pub expr: Option<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct IfStmt {
+    /// With a binding, `if let name = cond { ... }` runs the block if the optional `cond` has a value, bound to `name`.
+    pub binding: Option<String>,
+    pub cond: Expr,
+    pub then_block: Block,
+    /// Either another `if` for `else if` chains or a plain block.
+    pub else_branch: Option<Box<Stmt>>,
+    pub span: Span,
+}
+
+/// Loops can be labeled, `outer: while cond { ... }`, so that `break outer;` and `continue outer;` in nested loops
+/// target them. A label can't shadow the label of an enclosing loop.
+#[derive(Debug)]
+pub struct WhileStmt {
+    pub label: Option<String>,
+    pub cond: Expr,
+    pub body: Block,
+    pub span: Span,
+}
+
+/// `loop { ... }`, which only ends with `break`. As an expression, `let x := loop { ... break value; };`, it evaluates to
+/// the value of the `break` that leaves it.
+#[derive(Debug)]
+pub struct LoopStmt {
+    pub label: Option<String>,
+    pub body: Block,
+    pub span: Span,
+}
+
+/// `for name in iter { ... }`. The loop variable is scoped to the body and immutable unless declared with `var`.
+#[derive(Debug)]
+pub struct ForStmt {
+    pub label: Option<String>,
+    pub name: String,
+    pub is_mutable: bool,
+    pub iter: ForIter,
+    pub body: Block,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum ForIter {
+    /// `0..10`, `0..=10` or `10..0 step -2`. The step defaults to 1; a negative step counts down while the variable
+    /// is greater than the end (or equal to it for inclusive ranges).
+    Range { range: Range, step: Option<Expr> },
+    /// An array or a slice, iterated element by element.
+    Collection(Expr),
+}
+
+/// `switch subject { 1, 2 -> stmt; 3..9 -> { ... } else -> stmt; }`. Arms don't fall through unless they end with
+/// `fall;`, which continues with the body of the next arm.
+///
+/// Arms are tried in order and the first one whose patterns match, and whose guard holds, runs. A switch on an enum
+/// has to cover every variant, unless it has an `else` arm.
+#[derive(Debug)]
+pub struct SwitchStmt {
+    pub subject: Expr,
+    pub arms: Vec<SwitchArm>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct SwitchArm {
+    /// Alternatives separated by `,` or `|`, empty for the `else` arm. Alternatives bind the same variables.
+    pub cases: Vec<Pattern>,
+    /// `pattern if cond ->`, checked once a pattern matched, with its bindings in scope.
+    pub guard: Option<Expr>,
+    pub is_else: bool,
+    pub body: Block,
+    pub span: Span,
+}
+
+/// `break;`, `break label;` to leave an outer loop, or `break value;` to leave a `loop` expression with a value.
+#[derive(Debug)]
+pub struct BreakStmt {
+    pub target: BreakTarget,
+    pub span: Span,
+}
+
+/// What follows `break`. The parser can't tell `break name;` from a label, the checker resolves it as the label of an
+/// enclosing loop if there's one, as the value of the variable otherwise.
+#[derive(Debug)]
+pub enum BreakTarget {
+    None,
+    Name(String, Span),
+    Value(Expr),
+}
+
+/// `continue;`, or `continue label;` to go on with the next iteration of an outer loop.
+#[derive(Debug)]
+pub struct ContinueStmt {
+    pub label: Option<String>,
+    pub span: Span,
+}
+
+/// `defer stmt;` or `defer { ... }`. The deferred statement runs when the enclosing block exits, whether by falling
+/// through its end, `return`, `break` or `continue`, and only if the `defer` itself was reached. Deferred statements of
+/// a block run in reverse order, innermost blocks first.
+///
+/// The statement runs as if written at the exit, so it sees the variables as they are then. On `return`, the returned
+/// value is evaluated before any deferred statement runs, so deferred code can't change what the function returns.
+/// Deferred code can't leave its enclosing block itself: `return`, and `break` or `continue` targeting a loop outside
+/// of it, are rejected.
+#[derive(Debug)]
+pub struct DeferStmt {
+    pub stmt: Box<Stmt>,
+    pub span: Span,
+}
+
+/// Statement preceded by attributes.
+#[derive(Debug)]
+pub struct AttributedStmt {
+    pub attrs: Vec<Attribute>,
+    pub stmt: Box<Stmt>,
+}
+
+#[derive(Debug)]
+pub enum Stmt {
+    Var(VarDecl),
+    Let(LetDecl),
+    Expr(Expr),
+    Print(Expr),
+    Return(ReturnStmt),
+    If(IfStmt),
+    While(WhileStmt),
+    Loop(LoopStmt),
+    For(ForStmt),
+    Switch(SwitchStmt),
+    Fall(Span),
+    Break(BreakStmt),
+    Continue(ContinueStmt),
+    Defer(DeferStmt),
+    Block(Block),
+    Attributed(AttributedStmt),
+    /// Placeholder for a statement that failed to parse.
+    Error,
+}
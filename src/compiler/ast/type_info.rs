@@ -0,0 +1,29 @@
+use super::expr::{Expr, Path};
+use crate::compiler::diagnostic::Span;
+
+#[derive(Debug)]
+pub struct TypeInfo {
+    pub kind: TypeKind,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum TypeKind {
+    Base(String),
+    /// Type from an imported module, `vec::Vec3`.
+    Path(Path),
+    /// Generic struct with type arguments, `Pair<int, string>`.
+    Generic(Path, Vec<TypeInfo>),
+    /// Pointer `^T`.
+    Pointer(Box<TypeInfo>),
+    /// Optional `?T`, a `T` or `nil`.
+    Optional(Box<TypeInfo>),
+    /// Fixed-size array `[T; N]`.
+    Array(Box<TypeInfo>, Box<Expr>),
+    /// Slice `[T]`, a view into an array of any length.
+    Slice(Box<TypeInfo>),
+    /// Tuple `(int, string)`, with at least two elements.
+    Tuple(Vec<TypeInfo>),
+    /// Function type `fn(int, int) -> int`, without a return type for functions returning nothing.
+    Fn(Vec<TypeInfo>, Option<Box<TypeInfo>>),
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+
+/// What an attribute is written on.
+#[derive(Clone, Copy, PartialEq)]
+pub enum AttrTarget {
+    Function,
+    Method,
+    Struct,
+    Field,
+    Statement,
+}
+
+impl fmt::Display for AttrTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttrTarget::Function => write!(f, "functions"),
+            AttrTarget::Method => write!(f, "methods"),
+            AttrTarget::Struct => write!(f, "structs"),
+            AttrTarget::Field => write!(f, "fields"),
+            AttrTarget::Statement => write!(f, "statements"),
+        }
+    }
+}
+
+pub enum AttrArgs {
+    None,
+    /// A single string literal, one of the given values unless there are none.
+    Str(&'static [&'static str]),
+}
+
+pub struct AttrSpec {
+    pub name: &'static str,
+    pub targets: &'static [AttrTarget],
+    pub args: AttrArgs,
+}
+
+/// Attributes the compiler knows about. Any other attribute is an error.
+const KNOWN_ATTRIBUTES: &[AttrSpec] = &[
+    // A function run by the test runner, taking no parameters and returning nothing.
+    AttrSpec {
+        name: "test",
+        targets: &[AttrTarget::Function],
+        args: AttrArgs::None,
+    },
+    // A hint to inline the function at its call sites.
+    AttrSpec {
+        name: "inline",
+        targets: &[AttrTarget::Function, AttrTarget::Method],
+        args: AttrArgs::None,
+    },
+    // An item still available but about to be removed, with a message pointing to its replacement.
+    AttrSpec {
+        name: "deprecated",
+        targets: &[AttrTarget::Function, AttrTarget::Method, AttrTarget::Struct, AttrTarget::Field],
+        args: AttrArgs::Str(&[]),
+    },
+    // A function exported under its own name with the calling convention of the given ABI.
+    AttrSpec {
+        name: "extern",
+        targets: &[AttrTarget::Function],
+        args: AttrArgs::Str(&["c"]),
+    },
+];
+
+pub fn lookup(name: &str) -> Option<&'static AttrSpec> {
+    KNOWN_ATTRIBUTES.iter().find(|spec| spec.name == name)
+}
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use crate::compiler::ast::decl::{BindingTarget, ConstDecl, FnDecl};
+use crate::compiler::ast::expr::{BinaryOp, Expr, ExprKind, UnaryOp};
+use crate::compiler::ast::stmt::Stmt;
+use crate::compiler::diagnostic::Span;
+
+use super::Checker;
+
+/// Calls to const functions can nest this deep, which stops runaway recursion.
+const MAX_CALL_DEPTH: usize = 64;
+
+/// Expressions a constant evaluation can evaluate, which stops calls that branch out without nesting deeply.
+const MAX_STEPS: usize = 100_000;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConstValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+}
+
+/// A `const` declaration, evaluated the first time its value is needed so consts can refer to each other in any order.
+pub struct ConstInfo<'a> {
+    pub decl: &'a ConstDecl,
+    pub module: usize,
+    pub state: ConstState,
+}
+
+pub enum ConstState {
+    Pending,
+    Evaluating,
+    /// `None` if the evaluation failed, which has been reported at the declaration.
+    Done(Option<ConstValue>),
+}
+
+/// Why an expression has no value at compile time.
+pub enum ConstError {
+    /// Reported at the const being evaluated.
+    Error(String, Span),
+    /// Already reported, or a type error that the checker reports on its own.
+    Silent,
+}
+
+type ConstResult = Result<ConstValue, ConstError>;
+
+impl<'a> Checker<'a> {
+    /// Value of a const, evaluating it first if needed. A const that depends on itself is reported at its declaration.
+    pub(super) fn const_value(&mut self, key: &str) -> ConstResult {
+        let info = &self.consts[key];
+
+        match &info.state {
+            ConstState::Done(Some(value)) => return Ok(value.clone()),
+            ConstState::Done(None) => return Err(ConstError::Silent),
+            ConstState::Evaluating => {
+                let start = self.const_stack.iter().position(|name| name == key).unwrap();
+                let cycle = self.const_stack[start..].iter().chain([&key.to_string()]).cloned().collect::<Vec<_>>();
+                let (module, span) = (info.module, info.decl.span);
+
+                self.in_module(module, |checker| checker.error(format!("const '{key}' depends on itself: {}", cycle.join(" -> ")), span));
+                return Err(ConstError::Silent);
+            }
+            ConstState::Pending => {}
+        }
+
+        let (decl, module) = (info.decl, info.module);
+
+        // A const needed by another one shares its budget.
+        if self.const_stack.is_empty() {
+            self.const_steps = MAX_STEPS;
+        }
+
+        self.consts.get_mut(key).unwrap().state = ConstState::Evaluating;
+        self.const_stack.push(key.to_string());
+
+        let value = self.in_module(module, |checker| match checker.eval_const(&decl.expr, Some(&HashMap::new()), 0) {
+            Ok(value) => Some(value),
+            Err(ConstError::Error(message, span)) => {
+                checker.error(message, span);
+                None
+            }
+            Err(ConstError::Silent) => None,
+        });
+
+        self.const_stack.pop();
+        self.consts.get_mut(key).unwrap().state = ConstState::Done(value.clone());
+
+        value.ok_or(ConstError::Silent)
+    }
+
+    /// Value of an int expression if it's known at compile time, reporting nothing.
+    pub(super) fn const_int(&mut self, expr: &Expr) -> Option<i64> {
+        match self.eval_const_root(expr) {
+            Ok(ConstValue::Int(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Evaluates an expression in the scopes being checked, with a budget of its own.
+    pub(super) fn eval_const_root(&mut self, expr: &Expr) -> ConstResult {
+        self.const_steps = MAX_STEPS;
+        self.eval_const(expr, None, 0)
+    }
+
+    /// Evaluates an expression at compile time. `locals` holds the parameters and bindings of the const function being
+    /// evaluated, without it the expression is in the scopes being checked, where a local variable shadows a const.
+    pub(super) fn eval_const(&mut self, expr: &Expr, locals: Option<&HashMap<String, ConstValue>>, depth: usize) -> ConstResult {
+        if self.const_steps == 0 {
+            return Err(ConstError::Error(format!("constant evaluation exceeded {MAX_STEPS} steps"), expr.span));
+        }
+
+        self.const_steps -= 1;
+
+        match &expr.kind {
+            ExprKind::Int(value) => Ok(ConstValue::Int(*value as i64)),
+            ExprKind::Float(value) => Ok(ConstValue::Float(*value as f64)),
+            ExprKind::Bool(value) => Ok(ConstValue::Bool(*value)),
+            ExprKind::Str(value) => Ok(ConstValue::Str(value.clone())),
+            ExprKind::Ident(name) => {
+                let local = match locals {
+                    Some(locals) => locals.get(name).cloned(),
+                    None if self.scopes[1..].iter().any(|scope| scope.contains_key(name)) => return Err(ConstError::Error(format!("'{name}' is not a constant"), expr.span)),
+                    None => None,
+                };
+
+                match local {
+                    Some(value) => Ok(value),
+                    None => self.eval_const_name(self.current_module, name, &name.to_string(), expr.span),
+                }
+            }
+            ExprKind::Path(path) => match self.split_module(&path.segments) {
+                (module, [name]) => self.eval_const_name(module, name, &path.to_string(), expr.span),
+                _ => Err(ConstError::Error(format!("'{path}' is not a constant"), expr.span)),
+            },
+            ExprKind::Unary(op, operand) => {
+                let value = self.eval_const(operand, locals, depth)?;
+
+                match (op, value) {
+                    (UnaryOp::Neg, ConstValue::Int(value)) => Self::int(value.checked_neg(), expr.span),
+                    (UnaryOp::Neg, ConstValue::Float(value)) => Ok(ConstValue::Float(-value)),
+                    (UnaryOp::Not, ConstValue::Bool(value)) => Ok(ConstValue::Bool(!value)),
+                    (UnaryOp::AddrOf | UnaryOp::Deref, _) => Err(ConstError::Error("pointers can't be used in constant expressions".to_string(), expr.span)),
+                    _ => Err(ConstError::Silent),
+                }
+            }
+            ExprKind::Binary(op, left, right) => {
+                let left = self.eval_const(left, locals, depth)?;
+
+                match (op, &left) {
+                    (BinaryOp::And, ConstValue::Bool(false)) => return Ok(left),
+                    (BinaryOp::Or, ConstValue::Bool(true)) => return Ok(left),
+                    _ => {}
+                }
+
+                let right = self.eval_const(right, locals, depth)?;
+                Self::eval_binary(*op, left, right, expr.span)
+            }
+            ExprKind::Ternary(cond, then_expr, else_expr) => match self.eval_const(cond, locals, depth)? {
+                ConstValue::Bool(true) => self.eval_const(then_expr, locals, depth),
+                ConstValue::Bool(false) => self.eval_const(else_expr, locals, depth),
+                _ => Err(ConstError::Silent),
+            },
+            ExprKind::Call(callee, args) => {
+                let (module, name) = match &callee.kind {
+                    ExprKind::Ident(name) => (self.current_module, name.as_str()),
+                    ExprKind::Path(path) => match self.split_module(&path.segments) {
+                        (module, [name]) => (module, name.as_str()),
+                        _ => return Err(ConstError::Error("only const functions can be called in constant expressions".to_string(), callee.span)),
+                    },
+                    _ => return Err(ConstError::Error("only const functions can be called in constant expressions".to_string(), callee.span)),
+                };
+
+                let Some(&(fn_module, fn_decl)) = self.const_fns.get(&self.qualify(module, name)) else {
+                    return Err(ConstError::Error(format!("'{name}' is not a const function"), callee.span));
+                };
+
+                if depth == MAX_CALL_DEPTH {
+                    return Err(ConstError::Error(format!("constant evaluation exceeded {MAX_CALL_DEPTH} nested calls"), expr.span));
+                }
+
+                let mut values = HashMap::new();
+
+                for (i, arg) in args.iter().enumerate() {
+                    let Some(name) = arg.name.as_ref().or(fn_decl.params.get(i).map(|param| &param.name)) else {
+                        return Err(ConstError::Silent);
+                    };
+
+                    values.insert(name.clone(), self.eval_const(&arg.value, locals, depth)?);
+                }
+
+                // Defaults are evaluated where the function is declared.
+                for param in &fn_decl.params {
+                    if !values.contains_key(&param.name) {
+                        let Some(default) = &param.default else {
+                            return Err(ConstError::Silent);
+                        };
+
+                        let value = self.in_module(fn_module, |checker| checker.eval_const(default, None, depth))?;
+                        values.insert(param.name.clone(), value);
+                    }
+                }
+
+                // Errors in the body point at the call, which is where the const being evaluated is.
+                match self.in_module(fn_module, |checker| checker.eval_const_fn(fn_decl, values, depth + 1)) {
+                    Err(ConstError::Error(message, _)) => Err(ConstError::Error(message, expr.span)),
+                    result => result,
+                }
+            }
+            _ => Err(ConstError::Error("expression can't be evaluated at compile time".to_string(), expr.span)),
+        }
+    }
+
+    fn eval_const_name(&mut self, module: usize, name: &str, written: &str, span: Span) -> ConstResult {
+        let key = self.qualify(module, name);
+
+        if !self.consts.contains_key(&key) {
+            return Err(ConstError::Error(format!("'{written}' is not a constant"), span));
+        }
+
+        self.const_value(&key)
+    }
+
+    fn eval_const_fn(&mut self, fn_decl: &FnDecl, mut locals: HashMap<String, ConstValue>, depth: usize) -> ConstResult {
+        match self.eval_const_stmts(&fn_decl.stmts, &mut locals, depth)? {
+            Some(value) => Ok(value),
+            None => Err(ConstError::Silent),
+        }
+    }
+
+    /// Runs the statements of a const function, giving the returned value if they return.
+    fn eval_const_stmts(&mut self, stmts: &[Stmt], locals: &mut HashMap<String, ConstValue>, depth: usize) -> Result<Option<ConstValue>, ConstError> {
+        for stmt in stmts {
+            let returned = match stmt {
+                Stmt::Let(let_decl) => {
+                    let Some(expr) = &let_decl.expr else {
+                        return Err(ConstError::Silent);
+                    };
+
+                    let value = self.eval_const(expr, Some(locals), depth)?;
+
+                    // Tuples aren't constant values, so only a plain name can get here.
+                    let BindingTarget::Name(name) = &let_decl.target else {
+                        return Err(ConstError::Silent);
+                    };
+
+                    locals.insert(name.clone(), value);
+                    None
+                }
+                Stmt::Return(return_stmt) => match &return_stmt.expr {
+                    Some(expr) => Some(self.eval_const(expr, Some(locals), depth)?),
+                    None => return Err(ConstError::Silent),
+                },
+                Stmt::If(if_stmt) if if_stmt.binding.is_none() => match self.eval_const(&if_stmt.cond, Some(locals), depth)? {
+                    ConstValue::Bool(true) => self.eval_const_stmts(&if_stmt.then_block.stmts, &mut locals.clone(), depth)?,
+                    ConstValue::Bool(false) => match if_stmt.else_branch.as_deref() {
+                        Some(else_branch) => self.eval_const_stmts(std::slice::from_ref(else_branch), &mut locals.clone(), depth)?,
+                        None => None,
+                    },
+                    _ => return Err(ConstError::Silent),
+                },
+                Stmt::Block(block) => self.eval_const_stmts(&block.stmts, &mut locals.clone(), depth)?,
+                // Reported when the function is checked.
+                _ => return Err(ConstError::Silent),
+            };
+
+            if returned.is_some() {
+                return Ok(returned);
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn eval_binary(op: BinaryOp, left: ConstValue, right: ConstValue, span: Span) -> ConstResult {
+        use ConstValue::{Bool, Float, Int, Str};
+
+        let value = match (op, left, right) {
+            (BinaryOp::Add, Int(a), Int(b)) => return Self::int(a.checked_add(b), span),
+            (BinaryOp::Sub, Int(a), Int(b)) => return Self::int(a.checked_sub(b), span),
+            (BinaryOp::Mul, Int(a), Int(b)) => return Self::int(a.checked_mul(b), span),
+            (BinaryOp::Div, Int(_), Int(0)) => return Err(ConstError::Error("division by zero in constant expression".to_string(), span)),
+            (BinaryOp::Div, Int(a), Int(b)) => return Self::int(a.checked_div(b), span),
+            (BinaryOp::Add, Float(a), Float(b)) => Float(a + b),
+            (BinaryOp::Sub, Float(a), Float(b)) => Float(a - b),
+            (BinaryOp::Mul, Float(a), Float(b)) => Float(a * b),
+            (BinaryOp::Div, Float(a), Float(b)) => Float(a / b),
+            (BinaryOp::Add, Str(a), Str(b)) => Str(a + &b),
+            (BinaryOp::Less, Int(a), Int(b)) => Bool(a < b),
+            (BinaryOp::LessEq, Int(a), Int(b)) => Bool(a <= b),
+            (BinaryOp::Greater, Int(a), Int(b)) => Bool(a > b),
+            (BinaryOp::GreaterEq, Int(a), Int(b)) => Bool(a >= b),
+            (BinaryOp::Less, Float(a), Float(b)) => Bool(a < b),
+            (BinaryOp::LessEq, Float(a), Float(b)) => Bool(a <= b),
+            (BinaryOp::Greater, Float(a), Float(b)) => Bool(a > b),
+            (BinaryOp::GreaterEq, Float(a), Float(b)) => Bool(a >= b),
+            (BinaryOp::Eq, a, b) => Bool(a == b),
+            (BinaryOp::NotEq, a, b) => Bool(a != b),
+            (BinaryOp::And | BinaryOp::Or, Bool(_), Bool(b)) => Bool(b),
+            _ => return Err(ConstError::Silent),
+        };
+
+        Ok(value)
+    }
+
+    /// Result of int arithmetic, which has to fit in the 32 bits of an int.
+    fn int(value: Option<i64>, span: Span) -> ConstResult {
+        match value.filter(|value| i32::try_from(*value).is_ok()) {
+            Some(value) => Ok(ConstValue::Int(value)),
+            None => Err(ConstError::Error("overflow in constant expression".to_string(), span)),
+        }
+    }
+
+    /// Runs `f` as if checking the given module, so names resolve and diagnostics point there.
+    fn in_module<T>(&mut self, module: usize, f: impl FnOnce(&mut Self) -> T) -> T {
+        let current = std::mem::replace(&mut self.current_module, module);
+        let result = f(self);
+        self.current_module = current;
+
+        result
+    }
+}
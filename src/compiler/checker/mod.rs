@@ -0,0 +1,4580 @@
+mod attribute;
+mod const_eval;
+mod ty;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::compiler::ast::decl::{Attribute, BindingTarget, ConstDecl, Decl, EnumDecl, FnDecl, FnParam, ImplDecl, InterfaceDecl, StructDecl, TypeParam, VariantPayload};
+use crate::compiler::ast::expr::{Arg, BinaryOp, Closure, ClosureBody, Expr, ExprKind, FieldInit, Path, Range, UnaryOp, UpdateOp};
+use crate::compiler::ast::pattern::{FieldPattern, Pattern, PatternKind};
+use crate::compiler::ast::stmt::{AttributedStmt, Block, BreakStmt, BreakTarget, DeferStmt, ForIter, ForStmt, LoopStmt, ReturnStmt, Stmt, SwitchArm, SwitchStmt};
+use crate::compiler::ast::type_info::{TypeInfo, TypeKind};
+use crate::compiler::diagnostic::{Diagnostic, Span};
+use crate::compiler::loader::Module;
+
+use attribute::{AttrArgs, AttrTarget};
+use const_eval::{ConstInfo, ConstState, ConstValue};
+use ty::Ty;
+
+#[derive(Clone)]
+struct FnSig {
+    /// Type parameters of the function, preceded by those of the impl block for functions of a generic struct.
+    type_params: Vec<String>,
+    /// Doesn't include the `self` receiver of methods, and neither do the names and defaults.
+    params: Vec<Ty>,
+    param_names: Vec<String>,
+    /// Whether each parameter has a default value, which lets calls leave it out.
+    has_default: Vec<bool>,
+    /// Whether the last parameter is variadic, its type in `params` being the slice the extra arguments make up.
+    variadic: bool,
+    /// Interfaces the type arguments have to implement, as pairs of type parameter and interface name.
+    bounds: Vec<(String, String)>,
+    return_ty: Ty,
+    has_self: bool,
+    span: Span,
+}
+
+struct StructInfo {
+    type_params: Vec<String>,
+    fields: Vec<FieldInfo>,
+    span: Span,
+}
+
+#[derive(Clone)]
+struct FieldInfo {
+    name: String,
+    ty: Ty,
+    has_default: bool,
+}
+
+struct EnumInfo {
+    variants: Vec<VariantInfo>,
+    span: Span,
+}
+
+struct InterfaceInfo {
+    /// Method signatures in declaration order, `Self` being their first type parameter.
+    methods: Vec<(String, FnSig)>,
+    module: usize,
+    span: Span,
+}
+
+#[derive(Clone)]
+struct VariantInfo {
+    name: String,
+    payload: Payload,
+}
+
+#[derive(Clone)]
+enum Payload {
+    Unit,
+    Tuple(Vec<Ty>),
+    Struct(Vec<FieldInfo>),
+}
+
+struct Symbol {
+    ty: Ty,
+    /// Declared with `var`, so it can be assigned and updated.
+    is_mutable: bool,
+    span: Span,
+}
+
+/// Whether a place can be written to, and why not.
+enum Access {
+    Mutable,
+    /// A binding declared without `var`, in the given module.
+    Immutable {
+        name: String,
+        module: usize,
+        span: Span,
+    },
+    /// Not a place at all, described for the diagnostic.
+    Temporary(&'static str),
+}
+
+/// Value covered by a constant switch case, used to find duplicates.
+#[derive(PartialEq)]
+enum CaseValue {
+    /// Inclusive bounds, a single value has equal bounds.
+    Int(i64, i64),
+    Bool(bool),
+    Str(String),
+}
+
+/// What a pattern matches, to find unreachable patterns and check that switches on enums cover every variant.
+enum Coverage {
+    /// Any value, like `_` or a binding.
+    All,
+    /// Any value of the variant.
+    Variant(String),
+    /// Values of the variant whose payload matches.
+    PartOfVariant(String),
+    Value(CaseValue),
+    /// Values the checker doesn't track, like structs with a constant field.
+    Partial,
+    /// The pattern is invalid, so coverage isn't reported.
+    Error,
+}
+
+impl Coverage {
+    /// Coverage of a struct or variant pattern, which matches any value if each of its parts does.
+    fn of_parts(parts: Vec<Coverage>, variant: Option<String>) -> Coverage {
+        if parts.iter().any(|part| matches!(part, Coverage::Error)) {
+            return Coverage::Error;
+        }
+
+        match (variant, parts.iter().all(|part| matches!(part, Coverage::All))) {
+            (Some(name), true) => Coverage::Variant(name),
+            (Some(name), false) => Coverage::PartOfVariant(name),
+            (None, true) => Coverage::All,
+            (None, false) => Coverage::Partial,
+        }
+    }
+}
+
+/// What the arms of a switch checked so far cover.
+#[derive(Default)]
+struct SwitchCoverage {
+    /// Whether every value is covered, by an irrefutable pattern, `else` or all variants of an enum.
+    all: bool,
+    variants: Vec<String>,
+    values: Vec<CaseValue>,
+    has_errors: bool,
+}
+
+/// Loop enclosing the statement being checked.
+struct LoopScope {
+    label: Option<String>,
+    span: Span,
+    /// Whether it's a `loop` expression, which `break` leaves with a value.
+    is_expr: bool,
+    /// Type of the value of the first `break` leaving a loop expression, which the others have to match.
+    break_ty: Option<Ty>,
+}
+
+/// Declaration of an item only visible within its own module.
+struct InternalItem {
+    module: usize,
+    span: Span,
+}
+
+/// What the checker keeps of a module to resolve names while checking it.
+struct ModuleScope {
+    name: String,
+    file: String,
+    imports: HashMap<String, usize>,
+}
+
+/// Items of all modules live in the same tables, under their name qualified with the module name, `vec::Vec3`.
+/// Items of the root file keep their plain name.
+pub struct Checker<'a> {
+    modules: Vec<ModuleScope>,
+    current_module: usize,
+    functions: HashMap<String, FnSig>,
+    structs: HashMap<String, StructInfo>,
+    enums: HashMap<String, EnumInfo>,
+    interfaces: HashMap<String, InterfaceInfo>,
+    /// `impl Interface for Type` blocks, keyed by type name and interface name.
+    interface_impls: HashMap<(String, String), Span>,
+    /// Methods, associated functions and `init` constructors, keyed by type name and function name.
+    methods: HashMap<(String, String), FnSig>,
+    consts: HashMap<String, ConstInfo<'a>>,
+    /// Consts being evaluated, innermost last, to report cycles.
+    const_stack: Vec<String>,
+    /// Expressions the constant evaluation in progress can still evaluate.
+    const_steps: usize,
+    /// Declarations of const functions, which are run when evaluating consts, with their module.
+    const_fns: HashMap<String, (usize, &'a FnDecl)>,
+    /// Internal items by kind and qualified name. Fields and methods are qualified with their type, `geo::Point::x`.
+    internal_items: HashMap<(&'static str, String), InternalItem>,
+    scopes: Vec<HashMap<String, Symbol>>,
+    /// Type parameters of the generic item being checked.
+    type_params: Vec<String>,
+    /// Bounds of the type parameters of the function being checked.
+    bounds: Vec<(String, String)>,
+    /// Name of the function being checked, `None` in closures.
+    current_fn: Option<String>,
+    return_ty: Ty,
+    loops: Vec<LoopScope>,
+    switch_depth: usize,
+    /// Switches of the function being checked that cover every value of their subject, which count as returning if all
+    /// their arms return. Keyed by node, since spans don't tell modules apart.
+    exhaustive_switches: HashSet<*const SwitchStmt>,
+    /// Whether the statement being checked is deferred, where loops and switches outside of it can't be targeted.
+    in_defer: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Checker<'a> {
+    pub fn new() -> Self {
+        Checker {
+            modules: Vec::new(),
+            current_module: 0,
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            interfaces: HashMap::new(),
+            interface_impls: HashMap::new(),
+            methods: HashMap::new(),
+            consts: HashMap::new(),
+            const_stack: Vec::new(),
+            const_steps: 0,
+            const_fns: HashMap::new(),
+            internal_items: HashMap::new(),
+            scopes: vec![HashMap::new()],
+            type_params: Vec::new(),
+            bounds: Vec::new(),
+            current_fn: None,
+            return_ty: Ty::Void,
+            loops: Vec::new(),
+            switch_depth: 0,
+            exhaustive_switches: HashSet::new(),
+            in_defer: false,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn check(mut self, modules: &'a [Module]) -> Vec<Diagnostic> {
+        self.modules = modules
+            .iter()
+            .map(|module| ModuleScope {
+                name: module.name.clone(),
+                file: module.file.clone(),
+                imports: module.imports.clone(),
+            })
+            .collect();
+
+        // Imported modules are checked before their importers, so globals can be initialized from imported globals.
+        let order = Self::import_order(modules);
+        let decls = || order.iter().flat_map(|module| modules[*module].decls.iter().map(move |decl| (*module, decl)));
+        let structs = || decls().filter_map(|(module, decl)| if let Decl::Struct(struct_decl) = decl { Some((module, struct_decl)) } else { None });
+        let enums = || decls().filter_map(|(module, decl)| if let Decl::Enum(enum_decl) = decl { Some((module, enum_decl)) } else { None });
+        let fns = || decls().filter_map(|(module, decl)| if let Decl::Fn(fn_decl) = decl { Some((module, fn_decl)) } else { None });
+        let impls = || decls().filter_map(|(module, decl)| if let Decl::Impl(impl_decl) = decl { Some((module, impl_decl)) } else { None });
+        let interfaces = || decls().filter_map(|(module, decl)| if let Decl::Interface(interface_decl) = decl { Some((module, interface_decl)) } else { None });
+        let consts = || decls().filter_map(|(module, decl)| if let Decl::Const(const_decl) = decl { Some((module, const_decl)) } else { None });
+
+        // Consts are evaluated on demand from here on, as types can depend on them through array lengths.
+        for (module, const_decl) in consts() {
+            let name = self.qualify(module, &const_decl.name);
+            let state = ConstState::Pending;
+
+            self.consts.entry(name).or_insert(ConstInfo { decl: const_decl, module, state });
+        }
+
+        for (module, fn_decl) in fns().filter(|(_, fn_decl)| fn_decl.is_const) {
+            let name = self.qualify(module, &fn_decl.name);
+            self.const_fns.entry(name).or_insert((module, fn_decl));
+        }
+
+        // Type names go first, so field types and signatures can refer to any struct, enum or interface regardless of
+        // order.
+        for (module, decl) in decls() {
+            self.current_module = module;
+
+            match decl {
+                Decl::Struct(struct_decl) => {
+                    let name = self.qualify(module, &struct_decl.name);
+
+                    if self.check_type_name(&name, struct_decl.span) {
+                        let type_params = self.declare_type_params(&struct_decl.type_params, &[]);
+
+                        self.declare_internal("struct", &name, struct_decl.is_internal, struct_decl.span);
+                        self.structs.insert(
+                            name,
+                            StructInfo {
+                                type_params,
+                                fields: Vec::new(),
+                                span: struct_decl.span,
+                            },
+                        );
+                    }
+                }
+                Decl::Enum(enum_decl) => {
+                    let name = self.qualify(module, &enum_decl.name);
+
+                    if self.check_type_name(&name, enum_decl.span) {
+                        self.declare_internal("enum", &name, enum_decl.is_internal, enum_decl.span);
+                        self.enums.insert(
+                            name,
+                            EnumInfo {
+                                variants: Vec::new(),
+                                span: enum_decl.span,
+                            },
+                        );
+                    }
+                }
+                Decl::Interface(interface_decl) => {
+                    let name = self.qualify(module, &interface_decl.name);
+
+                    if self.check_type_name(&name, interface_decl.span) {
+                        self.declare_internal("interface", &name, interface_decl.is_internal, interface_decl.span);
+                        self.interfaces.insert(
+                            name,
+                            InterfaceInfo {
+                                methods: Vec::new(),
+                                module,
+                                span: interface_decl.span,
+                            },
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (module, struct_decl) in structs() {
+            self.current_module = module;
+            self.declare_struct_fields(struct_decl);
+        }
+
+        for (module, enum_decl) in enums() {
+            self.current_module = module;
+            self.declare_enum_variants(enum_decl);
+        }
+
+        for (module, interface_decl) in interfaces() {
+            self.current_module = module;
+            self.declare_interface(interface_decl);
+        }
+
+        for (module, fn_decl) in fns() {
+            self.current_module = module;
+            self.declare_fn(fn_decl);
+        }
+
+        for (module, impl_decl) in impls() {
+            self.current_module = module;
+            self.declare_impl(impl_decl);
+        }
+
+        // Const names are known before any global initializer or body is checked, whatever the order of declarations.
+        for (module, const_decl) in consts() {
+            self.current_module = module;
+
+            let ty = self.resolve_type(&const_decl.type_info);
+            self.define(&const_decl.name, ty, false, const_decl.span);
+
+            let name = self.qualify(module, &const_decl.name);
+            self.declare_internal("global", &name, const_decl.is_internal, const_decl.span);
+        }
+
+        for (module, decl) in decls() {
+            self.current_module = module;
+
+            let (target, is_internal, span) = match decl {
+                Decl::Var(var) => {
+                    self.check_binding(&var.target, true, var.type_info.as_ref(), var.expr.as_ref(), var.span);
+                    (&var.target, var.is_internal, var.span)
+                }
+                Decl::Let(let_decl) => {
+                    self.check_binding(&let_decl.target, false, let_decl.type_info.as_ref(), let_decl.expr.as_ref(), let_decl.span);
+                    (&let_decl.target, let_decl.is_internal, let_decl.span)
+                }
+                Decl::Const(const_decl) => {
+                    self.check_const(const_decl);
+                    continue;
+                }
+                Decl::Fn(_) | Decl::Struct(_) | Decl::Enum(_) | Decl::Impl(_) | Decl::Interface(_) | Decl::Import(_) | Decl::Error => continue,
+            };
+
+            for name in Self::target_names(target) {
+                let name = self.qualify(module, name);
+                self.declare_internal("global", &name, is_internal, span);
+            }
+        }
+
+        for (module, struct_decl) in structs() {
+            self.current_module = module;
+            self.check_struct_defaults(struct_decl);
+        }
+
+        for (module, fn_decl) in fns() {
+            self.current_module = module;
+            self.check_fn(fn_decl);
+        }
+
+        for (module, impl_decl) in impls() {
+            self.current_module = module;
+            self.check_impl(impl_decl);
+        }
+
+        self.diagnostics
+    }
+
+    /// Orders the modules so that each comes after the modules it imports. Imports can't be cyclic.
+    fn import_order(modules: &[Module]) -> Vec<usize> {
+        fn visit(module: usize, modules: &[Module], order: &mut Vec<usize>) {
+            if order.contains(&module) {
+                return;
+            }
+
+            let mut imports = modules[module].imports.values().copied().collect::<Vec<_>>();
+            imports.sort();
+
+            for import in imports {
+                visit(import, modules, order);
+            }
+
+            order.push(module);
+        }
+
+        let mut order = Vec::new();
+
+        if !modules.is_empty() {
+            visit(0, modules, &mut order);
+        }
+
+        order
+    }
+
+    fn declare_internal(&mut self, kind: &'static str, name: &str, is_internal: bool, span: Span) {
+        if is_internal {
+            let module = self.current_module;
+            self.internal_items.insert((kind, name.to_string()), InternalItem { module, span });
+        }
+    }
+
+    /// Reports access to an internal item from outside its module, `name` being the item as written at the use site.
+    fn check_visibility(&mut self, kind: &'static str, key: &str, name: &str, span: Span) {
+        let Some(item) = self.internal_items.get(&(kind, key.to_string())) else {
+            return;
+        };
+
+        if item.module == self.current_module {
+            return;
+        }
+
+        let module = &self.modules[item.module];
+        let note_file = (item.module > 0).then(|| module.file.clone());
+        let diagnostic = Diagnostic::error(format!("{kind} '{name}' is internal to module '{}'", module.name), span).with_note(format!("'{name}' is declared internal here"), item.span, note_file);
+
+        self.report(diagnostic);
+    }
+
+    /// Name under which an item declared in the module is stored.
+    fn qualify(&self, module: usize, name: &str) -> String {
+        match self.modules[module].name.as_str() {
+            "" => name.to_string(),
+            prefix => format!("{prefix}::{name}"),
+        }
+    }
+
+    /// Splits a leading imported module off a path, `vec::Vec3` giving the module imported as `vec` and `Vec3`. Any
+    /// other path is relative to the current module.
+    fn split_module<'p>(&self, segments: &'p [String]) -> (usize, &'p [String]) {
+        match segments.split_first() {
+            Some((first, rest)) if !rest.is_empty() => match self.modules[self.current_module].imports.get(first) {
+                Some(module) => (*module, rest),
+                None => (self.current_module, segments),
+            },
+            _ => (self.current_module, segments),
+        }
+    }
+
+    /// Structs, enums and interfaces share one namespace. Reports a duplicate and returns false if the name is already
+    /// taken.
+    fn check_type_name(&mut self, name: &str, span: Span) -> bool {
+        let kind = if self.structs.contains_key(name) {
+            "struct"
+        } else if self.enums.contains_key(name) {
+            "enum"
+        } else if self.interfaces.contains_key(name) {
+            "interface"
+        } else {
+            return true;
+        };
+
+        self.error(format!("{kind} '{name}' is already declared"), span);
+        false
+    }
+
+    /// Reports unknown attributes, attributes on items they don't apply to, duplicates and invalid arguments.
+    fn check_attributes(&mut self, attrs: &[Attribute], target: AttrTarget) {
+        for (i, attr) in attrs.iter().enumerate() {
+            let Some(spec) = attribute::lookup(&attr.name) else {
+                self.error(format!("unknown attribute '{}'", attr.name), attr.span);
+                continue;
+            };
+
+            if !spec.targets.contains(&target) {
+                self.error(format!("attribute '{}' can't be applied to {target}", attr.name), attr.span);
+                continue;
+            }
+
+            if attrs[..i].iter().any(|other| other.name == attr.name) {
+                self.error(format!("attribute '{}' is applied more than once", attr.name), attr.span);
+                continue;
+            }
+
+            match (&spec.args, &attr.args[..]) {
+                (AttrArgs::None, []) => {}
+                (AttrArgs::None, _) => self.error(format!("attribute '{}' takes no arguments", attr.name), attr.span),
+                (AttrArgs::Str(values), [Expr { kind: ExprKind::Str(value), span }]) => {
+                    if !values.is_empty() && !values.contains(&value.as_str()) {
+                        let expected = values.iter().map(|value| format!("\"{value}\"")).collect::<Vec<_>>();
+                        self.error(format!("unsupported value \"{value}\" for attribute '{}', expected {}", attr.name, expected.join(" or ")), *span);
+                    }
+                }
+                (AttrArgs::Str(_), _) => self.error(format!("attribute '{}' takes a single string literal argument", attr.name), attr.span),
+            }
+        }
+    }
+
+    /// Appends the type parameters of an item to the ones already in scope, reporting duplicates.
+    fn declare_type_params(&mut self, type_params: &[TypeParam], outer: &[String]) -> Vec<String> {
+        let mut names = outer.to_vec();
+
+        for type_param in type_params {
+            if names.contains(&type_param.name) {
+                self.error(format!("type parameter '{}' is already declared", type_param.name), type_param.span);
+                continue;
+            }
+
+            names.push(type_param.name.clone());
+        }
+
+        names
+    }
+
+    /// Resolves the interfaces bounding the type parameters of a function.
+    fn resolve_bounds(&mut self, type_params: &[TypeParam]) -> Vec<(String, String)> {
+        let mut bounds = Vec::<(String, String)>::new();
+
+        for type_param in type_params {
+            for path in &type_param.bounds {
+                if let Some(interface) = self.resolve_interface(path, type_param.span) {
+                    bounds.push((type_param.name.clone(), interface));
+                }
+            }
+        }
+
+        bounds
+    }
+
+    /// Structs and impl blocks take their type arguments as they are, only functions can require interfaces of them.
+    fn reject_bounds(&mut self, type_params: &[TypeParam]) {
+        for type_param in type_params.iter().filter(|type_param| !type_param.bounds.is_empty()) {
+            self.error(format!("type parameter '{}' can't have bounds, only those of functions can", type_param.name), type_param.span);
+        }
+    }
+
+    /// Resolves the name of an interface, possibly from an imported module.
+    fn resolve_interface(&mut self, path: &Path, span: Span) -> Option<String> {
+        if let (module, [name]) = self.split_module(&path.segments) {
+            let name = self.qualify(module, name);
+
+            if self.interfaces.contains_key(&name) {
+                self.check_visibility("interface", &name, &path.to_string(), span);
+                return Some(name);
+            }
+        }
+
+        self.error(format!("unknown interface '{path}'"), span);
+        None
+    }
+
+    fn declare_struct_fields(&mut self, struct_decl: &StructDecl) {
+        let mut fields = Vec::<FieldInfo>::new();
+
+        self.check_attributes(&struct_decl.attrs, AttrTarget::Struct);
+        self.reject_bounds(&struct_decl.type_params);
+
+        self.type_params = struct_decl.type_params.iter().map(|type_param| type_param.name.clone()).collect();
+
+        for field in &struct_decl.fields {
+            self.check_attributes(&field.attrs, AttrTarget::Field);
+
+            if fields.iter().any(|other| other.name == field.name) {
+                self.error(format!("field '{}' is already declared in struct '{}'", field.name, struct_decl.name), field.span);
+                continue;
+            }
+
+            fields.push(FieldInfo {
+                name: field.name.clone(),
+                ty: self.resolve_type(&field.type_info),
+                has_default: field.default.is_some(),
+            });
+        }
+
+        self.type_params.clear();
+
+        // A duplicate declaration was already reported, so its fields are dropped.
+        let name = self.qualify(self.current_module, &struct_decl.name);
+
+        for field in &struct_decl.fields {
+            self.declare_internal("field", &format!("{name}::{}", field.name), field.is_internal, field.span);
+        }
+
+        if let Some(info) = self.structs.get_mut(&name).filter(|info| info.span == struct_decl.span) {
+            info.fields = fields;
+        }
+    }
+
+    /// Discriminants are numbered from 0, or from the previous discriminant plus one, like in C. They only make sense
+    /// when every variant is a plain tag, so they're rejected as soon as one variant carries a payload.
+    fn declare_enum_variants(&mut self, enum_decl: &EnumDecl) {
+        let has_payload = enum_decl.variants.iter().any(|variant| !matches!(variant.payload, VariantPayload::Unit));
+        let mut variants = Vec::<VariantInfo>::new();
+        let mut discriminants = Vec::<(i64, &str)>::new();
+        let mut next_discriminant = 0;
+
+        for variant in &enum_decl.variants {
+            if variants.iter().any(|other| other.name == variant.name) {
+                self.error(format!("variant '{}' is already declared in enum '{}'", variant.name, enum_decl.name), variant.span);
+                continue;
+            }
+
+            let payload = match &variant.payload {
+                VariantPayload::Unit => Payload::Unit,
+                VariantPayload::Tuple(types) => Payload::Tuple(types.iter().map(|type_info| self.resolve_type(type_info)).collect()),
+                VariantPayload::Struct(fields) => {
+                    let mut infos = Vec::<FieldInfo>::new();
+
+                    for field in fields {
+                        self.check_attributes(&field.attrs, AttrTarget::Field);
+
+                        if infos.iter().any(|other| other.name == field.name) {
+                            self.error(format!("field '{}' is already declared in variant '{}::{}'", field.name, enum_decl.name, variant.name), field.span);
+                            continue;
+                        }
+
+                        if field.is_internal {
+                            self.error("variant fields can't be internal", field.span);
+                        }
+
+                        if let Some(default) = &field.default {
+                            self.error("variant fields can't have default values", default.span);
+                        }
+
+                        infos.push(FieldInfo {
+                            name: field.name.clone(),
+                            ty: self.resolve_type(&field.type_info),
+                            has_default: false,
+                        });
+                    }
+
+                    Payload::Struct(infos)
+                }
+            };
+
+            if let Some(expr) = &variant.discriminant {
+                if has_payload {
+                    self.error("explicit discriminants are only allowed on enums without payload variants", expr.span);
+                } else if let Some(value) = self.const_int(expr) {
+                    next_discriminant = value;
+                } else {
+                    self.error("enum discriminant must be an integer constant", expr.span);
+                }
+            }
+
+            if !has_payload {
+                if let Some((_, other)) = discriminants.iter().find(|(value, _)| *value == next_discriminant) {
+                    self.error(format!("discriminant value {next_discriminant} is already used by variant '{other}'"), variant.span);
+                }
+
+                discriminants.push((next_discriminant, &variant.name));
+                next_discriminant += 1;
+            }
+
+            variants.push(VariantInfo { name: variant.name.clone(), payload });
+        }
+
+        let name = self.qualify(self.current_module, &enum_decl.name);
+
+        if let Some(info) = self.enums.get_mut(&name).filter(|info| info.span == enum_decl.span) {
+            info.variants = variants;
+        }
+    }
+
+    /// Defaults are checked in the global scope, since they're evaluated wherever a literal omits the field.
+    fn check_struct_defaults(&mut self, struct_decl: &StructDecl) {
+        let name = self.qualify(self.current_module, &struct_decl.name);
+
+        let Some(info) = self.structs.get(&name).filter(|info| info.span == struct_decl.span) else {
+            return;
+        };
+
+        let field_types = info.fields.iter().map(|field| (field.name.clone(), field.ty.clone())).collect::<HashMap<_, _>>();
+
+        self.type_params = info.type_params.clone();
+
+        for (i, field) in struct_decl.fields.iter().enumerate() {
+            let is_duplicate = struct_decl.fields[..i].iter().any(|other| other.name == field.name);
+
+            if let (Some(default), false) = (&field.default, is_duplicate) {
+                let expected = field_types[&field.name].clone();
+                let actual = self.check_value(default);
+
+                if !expected.is_assignable_from(&actual) {
+                    self.error(format!("mismatched types: expected {expected}, found {actual}"), default.span);
+                }
+            }
+        }
+
+        self.type_params.clear();
+    }
+
+    /// Interface methods are resolved like methods of a generic type whose only type parameter is `Self`, bound to the
+    /// implementing type or to the type parameter a call goes through.
+    fn declare_interface(&mut self, interface_decl: &InterfaceDecl) {
+        let name = self.qualify(self.current_module, &interface_decl.name);
+
+        if !self.interfaces.get(&name).is_some_and(|info| info.span == interface_decl.span) {
+            return;
+        }
+
+        let mut methods = Vec::<(String, FnSig)>::new();
+        self.type_params = vec!["Self".to_string()];
+
+        for fn_decl in &interface_decl.methods {
+            let sig = self.resolve_sig(fn_decl, true);
+
+            if methods.iter().any(|(other, _)| *other == fn_decl.name) {
+                self.error(format!("'{}' is already declared in interface '{}'", fn_decl.name, interface_decl.name), fn_decl.span);
+                continue;
+            }
+
+            if !sig.has_self {
+                self.error(format!("interface method '{}' must take 'self'", fn_decl.name), fn_decl.span);
+            }
+
+            if !fn_decl.type_params.is_empty() {
+                self.error(format!("interface method '{}' can't be generic", fn_decl.name), fn_decl.span);
+            }
+
+            for default in fn_decl.params.iter().filter_map(|param| param.default.as_ref()) {
+                self.error("interface method parameters can't have default values", default.span);
+            }
+
+            methods.push((fn_decl.name.clone(), sig));
+        }
+
+        self.type_params.clear();
+        self.interfaces.get_mut(&name).unwrap().methods = methods;
+    }
+
+    fn declare_fn(&mut self, fn_decl: &FnDecl) {
+        let name = self.qualify(self.current_module, &fn_decl.name);
+
+        self.check_attributes(&fn_decl.attrs, AttrTarget::Function);
+
+        let is_test = fn_decl.attrs.iter().any(|attr| attr.name == "test");
+
+        if is_test && (!fn_decl.type_params.is_empty() || !fn_decl.params.is_empty() || fn_decl.return_type.is_some()) {
+            self.error(format!("test function '{}' can't take parameters or return a value", fn_decl.name), fn_decl.span);
+        }
+
+        if self.functions.contains_key(&name) {
+            self.error(format!("function '{}' is already declared", fn_decl.name), fn_decl.span);
+            return;
+        }
+
+        if fn_decl.is_const {
+            self.check_const_fn_stmts(&fn_decl.name, &fn_decl.stmts, fn_decl.span);
+
+            if let Some(param) = fn_decl.params.iter().find(|param| param.is_variadic) {
+                self.error(format!("const function '{}' can't have a variadic parameter", fn_decl.name), param.span);
+            }
+        }
+
+        let sig = self.resolve_sig(fn_decl, false);
+        self.declare_internal("function", &name, fn_decl.is_internal, fn_decl.span);
+        self.functions.insert(name, sig);
+    }
+
+    /// A const function is run by the compiler, so its body is limited to what it can evaluate.
+    fn check_const_fn_stmts(&mut self, name: &str, stmts: &[Stmt], span: Span) {
+        for stmt in stmts {
+            match stmt {
+                Stmt::Let(_) | Stmt::Return(_) => {}
+                Stmt::If(if_stmt) if if_stmt.binding.is_none() => {
+                    self.check_const_fn_stmts(name, &if_stmt.then_block.stmts, span);
+
+                    if let Some(else_branch) = &if_stmt.else_branch {
+                        self.check_const_fn_stmts(name, std::slice::from_ref(else_branch), span);
+                    }
+                }
+                Stmt::Block(block) => self.check_const_fn_stmts(name, &block.stmts, span),
+                _ => {
+                    self.error(format!("const function '{name}' can only contain 'let', 'if' and 'return' statements"), span);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Checks the type of a const and evaluates it, reporting why it can't be computed at compile time.
+    fn check_const(&mut self, const_decl: &ConstDecl) {
+        let key = self.qualify(self.current_module, &const_decl.name);
+
+        if !std::ptr::eq(self.consts[&key].decl, const_decl) {
+            return;
+        }
+
+        let declared = self.scopes[0][&key].ty.clone();
+        let actual = self.check_value(&const_decl.expr);
+
+        if !matches!(declared, Ty::Int | Ty::Float | Ty::Bool | Ty::Str | Ty::Error) {
+            self.error(
+                format!("const '{}' must be an int, float, bool or string, found {declared}", const_decl.name),
+                const_decl.type_info.span,
+            );
+        } else if !declared.is_assignable_from(&actual) {
+            self.error(format!("mismatched types: expected {declared}, found {actual}"), const_decl.expr.span);
+        } else if actual != Ty::Error {
+            let _ = self.const_value(&key);
+        }
+    }
+
+    /// Resolves the signature of a function, or of a method when `in_impl` is set, where a leading `self` parameter
+    /// is the receiver rather than a regular parameter. The type parameters of the impl block are already in scope.
+    fn resolve_sig(&mut self, fn_decl: &FnDecl, in_impl: bool) -> FnSig {
+        let outer = self.type_params.clone();
+        let type_params = self.declare_type_params(&fn_decl.type_params, &outer);
+        let bounds = self.resolve_bounds(&fn_decl.type_params);
+        let mut params = Vec::<Ty>::new();
+        let mut param_names = Vec::<String>::new();
+        let mut has_default = Vec::<bool>::new();
+
+        self.type_params = type_params.clone();
+        let mut has_self = false;
+
+        for (i, param) in fn_decl.params.iter().enumerate() {
+            if !param.is_self() {
+                let ty = self.resolve_param_type(param.type_info.as_ref());
+                params.push(if param.is_variadic { Ty::Slice(Box::new(ty)) } else { ty });
+                param_names.push(param.name.clone());
+                has_default.push(param.default.is_some());
+            } else if !in_impl {
+                self.error("'self' parameter is only allowed in methods", param.span);
+            } else if i > 0 {
+                self.error("'self' must be the first parameter", param.span);
+            } else if param.type_info.is_some() {
+                self.error("'self' parameter can't have a type", param.span);
+            } else if fn_decl.name == "init" {
+                self.error("'init' can't take 'self', it's available in the body", param.span);
+            } else {
+                has_self = true;
+            }
+        }
+
+        let return_ty = match &fn_decl.return_type {
+            Some(type_info) => self.resolve_type(type_info),
+            None => Ty::Void,
+        };
+
+        self.type_params = outer;
+
+        FnSig {
+            type_params,
+            params,
+            param_names,
+            has_default,
+            variadic: fn_decl.params.last().is_some_and(|param| param.is_variadic),
+            bounds,
+            return_ty,
+            has_self,
+            span: fn_decl.span,
+        }
+    }
+
+    /// Methods can be spread over several impl blocks of the same type.
+    fn declare_impl(&mut self, impl_decl: &ImplDecl) {
+        let type_name = &self.qualify(self.current_module, &impl_decl.type_name);
+
+        let (kind, type_param_count) = match (self.structs.get(type_name), self.enums.contains_key(type_name)) {
+            (Some(info), _) => ("struct", info.type_params.len()),
+            (None, true) => ("enum", 0),
+            (None, false) => {
+                self.error(format!("unknown type '{}'", impl_decl.type_name), impl_decl.span);
+                return;
+            }
+        };
+
+        if impl_decl.type_params.len() != type_param_count {
+            let message = match type_param_count {
+                0 => format!("{kind} '{}' is not generic", impl_decl.type_name),
+                count => format!("impl of '{}' expects {count} type parameters, found {}", impl_decl.type_name, impl_decl.type_params.len()),
+            };
+
+            self.error(message, impl_decl.span);
+            return;
+        }
+
+        self.reject_bounds(&impl_decl.type_params);
+        self.type_params = self.declare_type_params(&impl_decl.type_params, &[]);
+
+        for fn_decl in &impl_decl.fns {
+            let key = (type_name.clone(), fn_decl.name.clone());
+
+            self.check_attributes(&fn_decl.attrs, AttrTarget::Method);
+
+            if fn_decl.name == "init" && self.enums.contains_key(type_name) {
+                self.error(format!("enum '{}' can't have an 'init' constructor", impl_decl.type_name), fn_decl.span);
+            } else if self.methods.contains_key(&key) {
+                self.error(format!("'{}' is already declared for type '{}'", fn_decl.name, impl_decl.type_name), fn_decl.span);
+            } else if self.enums.get(type_name).is_some_and(|info| info.variants.iter().any(|variant| variant.name == fn_decl.name)) {
+                self.error(format!("'{}' is already a variant of enum '{}'", fn_decl.name, impl_decl.type_name), fn_decl.span);
+            } else {
+                let sig = self.resolve_sig(fn_decl, true);
+                self.declare_internal("method", &format!("{type_name}::{}", fn_decl.name), fn_decl.is_internal, fn_decl.span);
+                self.methods.insert(key, sig);
+            }
+        }
+
+        if let Some(interface) = &impl_decl.interface {
+            self.check_interface_impl(impl_decl, interface, type_name);
+        }
+
+        self.type_params.clear();
+    }
+
+    /// Checks that `impl Interface for Type` defines exactly the methods of the interface, with the signatures it
+    /// declares once `Self` is the type.
+    fn check_interface_impl(&mut self, impl_decl: &ImplDecl, path: &Path, type_name: &str) {
+        let Some(interface) = self.resolve_interface(path, impl_decl.span) else {
+            return;
+        };
+
+        let key = (type_name.to_string(), interface.clone());
+
+        if let Some(&first) = self.interface_impls.get(&key) {
+            let message = format!("interface '{path}' is already implemented for type '{}'", impl_decl.type_name);
+            let note_file = (self.current_module > 0).then(|| self.modules[self.current_module].file.clone());
+            let diagnostic = Diagnostic::error(message, impl_decl.span).with_note(format!("'{path}' is first implemented here"), first, note_file);
+
+            self.report(diagnostic);
+            return;
+        }
+
+        self.interface_impls.insert(key, impl_decl.span);
+
+        let info = &self.interfaces[&interface];
+        let methods = info.methods.clone();
+        let note_file = (info.module > 0).then(|| self.modules[info.module].file.clone());
+
+        let self_ty = match self.structs.contains_key(type_name) {
+            true => Ty::Struct(type_name.to_string(), self.type_params.iter().map(|type_param| Ty::Param(type_param.clone())).collect()),
+            false => Ty::Enum(type_name.to_string()),
+        };
+
+        let bindings = HashMap::from([("Self".to_string(), self_ty)]);
+        let mut missing = Vec::<String>::new();
+
+        for (name, expected) in &methods {
+            let Some(fn_decl) = impl_decl.fns.iter().find(|fn_decl| fn_decl.name == *name) else {
+                missing.push(format!("'{name}'"));
+                continue;
+            };
+
+            let Some(actual) = self.methods.get(&(type_name.to_string(), name.clone())).filter(|sig| sig.span == fn_decl.span).cloned() else {
+                continue;
+            };
+
+            let skip = impl_decl.type_params.len();
+
+            if !Self::sig_matches(expected, &actual, skip, &bindings) {
+                let expected = Self::sig_text(expected, 1, &bindings);
+                let actual = Self::sig_text(&actual, skip, &HashMap::new());
+                let message = format!("method '{name}' doesn't match interface '{path}': expected {expected}, found {actual}");
+                let span = methods.iter().find(|(other, _)| other == name).unwrap().1.span;
+
+                self.report(Diagnostic::error(message, fn_decl.span).with_note(format!("'{name}' is declared here"), span, note_file.clone()));
+            }
+        }
+
+        for fn_decl in impl_decl.fns.iter().filter(|fn_decl| !methods.iter().any(|(name, _)| *name == fn_decl.name)) {
+            self.error(format!("'{}' is not a method of interface '{path}'", fn_decl.name), fn_decl.span);
+        }
+
+        if !missing.is_empty() {
+            self.error(format!("missing methods {} in impl of '{path}' for '{}'", missing.join(", "), impl_decl.type_name), impl_decl.span);
+        }
+    }
+
+    /// Whether a method of an impl has the signature its interface declares, once `Self` is bound by `bindings`. The
+    /// type parameters of the method's type, the first `skip` ones, aren't its own.
+    fn sig_matches(expected: &FnSig, actual: &FnSig, skip: usize, bindings: &HashMap<String, Ty>) -> bool {
+        expected.type_params[1..] == actual.type_params[skip..]
+            && expected.has_self == actual.has_self
+            && expected.variadic == actual.variadic
+            && expected.params.len() == actual.params.len()
+            && expected.params.iter().zip(&actual.params).all(|(expected, actual)| expected.substitute(bindings) == *actual)
+            && expected.return_ty.substitute(bindings) == actual.return_ty
+    }
+
+    /// Signature of a method as shown in diagnostics, `fn<T>(self, int, ...string) -> T`, skipping the type
+    /// parameters of its type.
+    fn sig_text(sig: &FnSig, skip: usize, bindings: &HashMap<String, Ty>) -> String {
+        let type_params = match &sig.type_params[skip..] {
+            [] => String::new(),
+            own => format!("<{}>", own.join(", ")),
+        };
+
+        let mut params = sig.params.iter().map(|param| param.substitute(bindings).to_string()).collect::<Vec<_>>();
+
+        if let (true, Some(Ty::Slice(elem))) = (sig.variadic, sig.params.last()) {
+            *params.last_mut().unwrap() = format!("...{}", elem.substitute(bindings));
+        }
+
+        if sig.has_self {
+            params.insert(0, "self".to_string());
+        }
+
+        match sig.return_ty.substitute(bindings) {
+            Ty::Void => format!("fn{type_params}({})", params.join(", ")),
+            return_ty => format!("fn{type_params}({}) -> {return_ty}", params.join(", ")),
+        }
+    }
+
+    fn check_fn(&mut self, fn_decl: &FnDecl) {
+        // Duplicate declarations aren't in the function table, so their signature is resolved here.
+        let sig = match self.functions.get(&self.qualify(self.current_module, &fn_decl.name)) {
+            Some(sig) if sig.span == fn_decl.span => sig.clone(),
+            _ => self.resolve_sig(fn_decl, false),
+        };
+
+        self.check_fn_body(fn_decl, sig, fn_decl.name.clone(), None);
+    }
+
+    /// `self` is the receiver in methods, and the value under construction in `init`. In the impl of a generic struct,
+    /// its type arguments are the type parameters of the impl block.
+    fn check_impl(&mut self, impl_decl: &ImplDecl) {
+        let type_name = self.qualify(self.current_module, &impl_decl.type_name);
+        let type_args = impl_decl.type_params.iter().map(|type_param| Ty::Param(type_param.name.clone())).collect();
+
+        let self_ty = match &type_name {
+            name if self.structs.contains_key(name) => Ty::Struct(name.clone(), type_args),
+            name if self.enums.contains_key(name) => Ty::Enum(name.clone()),
+            _ => return,
+        };
+
+        for fn_decl in &impl_decl.fns {
+            let key = (type_name.clone(), fn_decl.name.clone());
+
+            let sig = match self.methods.get(&key) {
+                Some(sig) if sig.span == fn_decl.span => sig.clone(),
+                _ => continue,
+            };
+
+            let has_self = sig.has_self || fn_decl.name == "init";
+            let name = format!("{}::{}", impl_decl.type_name, fn_decl.name);
+
+            self.check_fn_body(fn_decl, sig, name, has_self.then(|| self_ty.clone()));
+        }
+    }
+
+    fn check_fn_body(&mut self, fn_decl: &FnDecl, sig: FnSig, name: String, self_ty: Option<Ty>) {
+        self.current_fn = Some(name.clone());
+        self.type_params = sig.type_params;
+        self.bounds = sig.bounds;
+        self.return_ty = sig.return_ty;
+        self.exhaustive_switches.clear();
+
+        self.check_params(&fn_decl.params, &sig.params);
+        self.scopes.push(HashMap::new());
+
+        // The value under construction in `init` can always be modified, a receiver only if it's declared `var self`.
+        if let Some(self_ty) = self_ty {
+            match fn_decl.params.iter().find(|param| param.is_self()) {
+                Some(param) => self.define("self", self_ty, param.is_mutable, param.span),
+                None => self.define("self", self_ty, true, fn_decl.span),
+            }
+        }
+
+        for (param, ty) in fn_decl.params.iter().filter(|param| !param.is_self()).zip(sig.params) {
+            self.define(&param.name, ty, param.is_mutable, param.span);
+        }
+
+        for stmt in &fn_decl.stmts {
+            self.check_stmt(stmt);
+        }
+
+        self.scopes.pop();
+
+        if self.return_ty != Ty::Void && !self.stmts_return(&fn_decl.stmts) {
+            self.error(format!("missing return in function '{name}' returning {}", self.return_ty), fn_decl.span);
+        }
+
+        self.current_fn = None;
+        self.type_params.clear();
+        self.bounds.clear();
+    }
+
+    /// Defaults are checked before the parameters are in scope, since they can't refer to them.
+    fn check_params(&mut self, params: &[FnParam], tys: &[Ty]) {
+        let mut after_default = false;
+
+        for (i, (param, ty)) in params.iter().filter(|param| !param.is_self()).zip(tys).enumerate() {
+            if param.is_variadic {
+                if i + 1 < tys.len() {
+                    self.error(format!("variadic parameter '{}' must be the last parameter", param.name), param.span);
+                }
+
+                if let Some(default) = &param.default {
+                    self.error(format!("variadic parameter '{}' can't have a default value", param.name), default.span);
+                }
+
+                continue;
+            }
+
+            let Some(default) = &param.default else {
+                if after_default {
+                    self.error(format!("parameter '{}' needs a default value, it follows a parameter with one", param.name), param.span);
+                }
+
+                continue;
+            };
+
+            after_default = true;
+            let default_ty = self.check_value(default);
+
+            if !ty.is_assignable_from(&default_ty) {
+                self.error(format!("mismatched types: expected {ty}, found {default_ty}"), default.span);
+            }
+        }
+    }
+
+    fn check_binding(&mut self, target: &BindingTarget, is_mutable: bool, type_info: Option<&TypeInfo>, expr: Option<&Expr>, span: Span) {
+        let declared = type_info.map(|type_info| self.resolve_type(type_info));
+        let actual = expr.map(|expr| self.check_value(expr));
+
+        if let (Some(declared), Some(actual)) = (&declared, &actual) {
+            if !declared.is_assignable_from(actual) {
+                self.error(format!("mismatched types: expected {declared}, found {actual}"), expr.unwrap().span);
+            }
+        }
+
+        let ty = if declared.is_none() && actual == Some(Ty::Nil) {
+            self.error(format!("cannot infer the type of '{target}' from 'nil', add a type annotation"), expr.unwrap().span);
+            Ty::Error
+        } else {
+            declared.or(actual).unwrap_or(Ty::Error)
+        };
+
+        let names = match target {
+            BindingTarget::Name(name) => {
+                self.define(name, ty, is_mutable, span);
+                return;
+            }
+            BindingTarget::Tuple(names) => names,
+        };
+
+        let elem_tys = match ty {
+            Ty::Tuple(elems) if elems.len() == names.len() => elems,
+            Ty::Error => vec![Ty::Error; names.len()],
+            ty => {
+                self.error(format!("cannot destructure value of type {ty} into {} variables", names.len()), span);
+                vec![Ty::Error; names.len()]
+            }
+        };
+
+        for (name, ty) in names.iter().zip(elem_tys) {
+            if name != "_" {
+                self.define(name, ty, is_mutable, span);
+            }
+        }
+    }
+
+    /// Names of the variables a `var` or `let` declares.
+    fn target_names(target: &BindingTarget) -> Vec<&String> {
+        match target {
+            BindingTarget::Name(name) => vec![name],
+            BindingTarget::Tuple(names) => names.iter().filter(|name| *name != "_").collect(),
+        }
+    }
+
+    fn check_block(&mut self, block: &Block) {
+        self.scopes.push(HashMap::new());
+
+        for stmt in &block.stmts {
+            self.check_stmt(stmt);
+        }
+
+        self.scopes.pop();
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Var(var) => self.check_binding(&var.target, true, var.type_info.as_ref(), var.expr.as_ref(), var.span),
+            Stmt::Let(let_decl) => self.check_binding(&let_decl.target, false, let_decl.type_info.as_ref(), let_decl.expr.as_ref(), let_decl.span),
+            Stmt::Expr(expr) => {
+                self.check_expr(expr);
+            }
+            Stmt::Print(expr) => {
+                self.check_value(expr);
+            }
+            Stmt::Return(return_stmt) => self.check_return(return_stmt),
+            Stmt::If(if_stmt) => {
+                match &if_stmt.binding {
+                    Some(name) => self.check_if_let(name, &if_stmt.cond, &if_stmt.then_block, if_stmt.span),
+                    None => {
+                        self.check_condition(&if_stmt.cond);
+                        self.check_block(&if_stmt.then_block);
+                    }
+                }
+
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    self.check_stmt(else_branch);
+                }
+            }
+            Stmt::While(while_stmt) => {
+                self.check_condition(&while_stmt.cond);
+                self.check_loop_body(while_stmt.label.as_ref(), &while_stmt.body, while_stmt.span);
+            }
+            Stmt::Loop(loop_stmt) => self.check_loop_body(loop_stmt.label.as_ref(), &loop_stmt.body, loop_stmt.span),
+            Stmt::For(for_stmt) => self.check_for(for_stmt),
+            Stmt::Switch(switch_stmt) => self.check_switch(switch_stmt),
+            Stmt::Fall(span) => {
+                if self.switch_depth == 0 && self.in_defer {
+                    self.error("'fall' can't leave deferred code", *span);
+                } else if self.switch_depth == 0 {
+                    self.error("'fall' outside of a switch", *span);
+                } else {
+                    self.error("'fall' must be the last statement of a switch arm", *span);
+                }
+            }
+            Stmt::Break(break_stmt) => self.check_break(break_stmt),
+            Stmt::Continue(continue_stmt) => {
+                self.jump_target("continue", continue_stmt.label.as_deref(), continue_stmt.span);
+            }
+            Stmt::Defer(defer_stmt) => self.check_defer(defer_stmt),
+            Stmt::Attributed(AttributedStmt { attrs, stmt }) => {
+                self.check_attributes(attrs, AttrTarget::Statement);
+                self.check_stmt(stmt);
+            }
+            Stmt::Block(block) => self.check_block(block),
+            Stmt::Error => {}
+        }
+    }
+
+    /// Deferred code runs at the exits of the enclosing block, so it can't jump anywhere itself, and a declaration
+    /// would go out of scope as soon as it's made.
+    fn check_defer(&mut self, defer_stmt: &DeferStmt) {
+        if matches!(*defer_stmt.stmt, Stmt::Var(_) | Stmt::Let(_)) {
+            self.error("variable declarations can't be deferred", defer_stmt.span);
+            return;
+        }
+
+        let loops = std::mem::take(&mut self.loops);
+        let switch_depth = std::mem::replace(&mut self.switch_depth, 0);
+        let in_defer = std::mem::replace(&mut self.in_defer, true);
+
+        self.scopes.push(HashMap::new());
+        self.check_stmt(&defer_stmt.stmt);
+        self.scopes.pop();
+
+        self.loops = loops;
+        self.switch_depth = switch_depth;
+        self.in_defer = in_defer;
+    }
+
+    fn check_loop_body(&mut self, label: Option<&String>, body: &Block, span: Span) {
+        self.enter_loop(label, false, span);
+        self.check_block(body);
+        self.loops.pop();
+    }
+
+    /// The type of a `loop` expression is the type of the values its `break`s leave it with.
+    fn check_loop_expr(&mut self, loop_stmt: &LoopStmt) -> Ty {
+        self.enter_loop(loop_stmt.label.as_ref(), true, loop_stmt.span);
+        self.check_block(&loop_stmt.body);
+
+        match self.loops.pop().and_then(|scope| scope.break_ty) {
+            Some(ty) => ty,
+            None => {
+                self.error("'loop' expression needs a 'break' with a value", loop_stmt.span);
+                Ty::Error
+            }
+        }
+    }
+
+    fn enter_loop(&mut self, label: Option<&String>, is_expr: bool, span: Span) {
+        if let Some(outer) = self.loops.iter().find(|outer| outer.label.as_ref() == label && label.is_some()) {
+            let note_file = (self.current_module > 0).then(|| self.modules[self.current_module].file.clone());
+            let label = label.unwrap();
+            let diagnostic = Diagnostic::error(format!("label '{label}' shadows the label of an enclosing loop"), span).with_note(format!("'{label}' is declared here"), outer.span, note_file);
+
+            self.report(diagnostic);
+        }
+
+        self.loops.push(LoopScope {
+            label: label.cloned(),
+            span,
+            is_expr,
+            break_ty: None,
+        });
+    }
+
+    /// Index of the loop that `break` or `continue` leaves, the innermost one unless a label is given.
+    fn jump_target(&mut self, keyword: &str, label: Option<&str>, span: Span) -> Option<usize> {
+        if self.loops.is_empty() && self.in_defer {
+            self.error(format!("'{keyword}' can't leave deferred code"), span);
+            return None;
+        }
+
+        if self.loops.is_empty() {
+            self.error(format!("'{keyword}' outside of a loop"), span);
+            return None;
+        }
+
+        let Some(label) = label else {
+            return Some(self.loops.len() - 1);
+        };
+
+        let target = self.loops.iter().rposition(|scope| scope.label.as_deref() == Some(label));
+
+        if target.is_none() {
+            self.error(format!("unknown label '{label}'"), span);
+        }
+
+        target
+    }
+
+    /// `break name;` leaves the loop labeled `name` if there's one, and gives the value of the variable `name` otherwise.
+    fn check_break(&mut self, break_stmt: &BreakStmt) {
+        let label = match &break_stmt.target {
+            BreakTarget::Name(name, _) if self.loops.iter().any(|scope| scope.label.as_ref() == Some(name)) || self.lookup(name).is_none() => Some(name.as_str()),
+            _ => None,
+        };
+
+        let target = self.jump_target("break", label, break_stmt.span);
+
+        let value = match &break_stmt.target {
+            BreakTarget::Name(name, span) if label.is_none() => Some((self.check_ident(name, *span), *span)),
+            BreakTarget::Value(expr) => Some((self.check_value(expr), expr.span)),
+            _ => None,
+        };
+
+        let Some(target) = target else {
+            return;
+        };
+
+        let scope = &mut self.loops[target];
+
+        match value {
+            Some((_, span)) if !scope.is_expr => self.error("'break' with a value can only leave a 'loop' expression", span),
+            Some((ty, span)) => match &scope.break_ty {
+                Some(expected) if !expected.is_assignable_from(&ty) => {
+                    let message = format!("mismatched types: expected {expected}, found {ty}");
+                    self.error(message, span);
+                }
+                Some(_) => {}
+                None => scope.break_ty = Some(ty),
+            },
+            None if scope.is_expr => self.error("'break' out of a 'loop' expression needs a value", break_stmt.span),
+            None => {}
+        }
+    }
+
+    /// The loop variable shares the scope of the body's top-level statements, so the body can't redeclare it, and it
+    /// isn't visible after the loop.
+    fn check_for(&mut self, for_stmt: &ForStmt) {
+        let elem_ty = match &for_stmt.iter {
+            ForIter::Range { range, step } => {
+                self.check_int(&range.start, "range bound");
+                self.check_int(&range.end, "range bound");
+
+                if let Some(step) = step {
+                    self.check_int(step, "loop step");
+
+                    if self.const_int(step) == Some(0) {
+                        self.error("loop step can't be zero", step.span);
+                    }
+                }
+
+                Ty::Int
+            }
+            ForIter::Collection(expr) => match self.check_value(expr) {
+                Ty::Array(elem, _) | Ty::Slice(elem) => *elem,
+                Ty::Error => Ty::Error,
+                ty => {
+                    self.error(format!("cannot iterate over value of type {ty}"), expr.span);
+                    Ty::Error
+                }
+            },
+        };
+
+        self.scopes.push(HashMap::new());
+        self.define(&for_stmt.name, elem_ty, for_stmt.is_mutable, for_stmt.span);
+        self.enter_loop(for_stmt.label.as_ref(), false, for_stmt.span);
+
+        for stmt in &for_stmt.body.stmts {
+            self.check_stmt(stmt);
+        }
+
+        self.loops.pop();
+        self.scopes.pop();
+    }
+
+    fn check_switch(&mut self, switch_stmt: &SwitchStmt) {
+        let subject_ty = self.check_value(&switch_stmt.subject);
+
+        if !matches!(subject_ty, Ty::Int | Ty::Bool | Ty::Str | Ty::Enum(_) | Ty::Struct(..) | Ty::Error) {
+            self.error(format!("cannot switch on value of type {subject_ty}"), switch_stmt.subject.span);
+        }
+
+        let mut covered = SwitchCoverage::default();
+        let mut falls_into = None;
+        self.switch_depth += 1;
+
+        for (i, arm) in switch_stmt.arms.iter().enumerate() {
+            let is_last = i + 1 == switch_stmt.arms.len();
+
+            if arm.is_else && !is_last {
+                self.error("'else' must be the last switch arm", arm.span);
+            }
+
+            if covered.all {
+                self.error("unreachable switch arm", arm.span);
+            }
+
+            self.scopes.push(HashMap::new());
+
+            let bindings = self.check_arm_patterns(arm, &subject_ty, &mut covered);
+
+            if let (Some(span), false) = (falls_into, bindings.is_empty()) {
+                self.error("'fall' can't continue into an arm that binds variables", span);
+            }
+
+            for (name, ty, span) in bindings {
+                self.define(&name, ty, false, span);
+            }
+
+            if let Some(guard) = &arm.guard {
+                self.check_condition(guard);
+            }
+
+            let (last, rest) = match arm.body.stmts.split_last() {
+                Some((Stmt::Fall(span), rest)) => (Some(*span), rest),
+                _ => (None, &arm.body.stmts[..]),
+            };
+
+            for stmt in rest {
+                self.check_stmt(stmt);
+            }
+
+            self.scopes.pop();
+
+            if arm.is_else {
+                covered.all = true;
+            }
+
+            if let (Some(span), true) = (last, is_last) {
+                self.error("'fall' in the last switch arm has no arm to fall into", span);
+            }
+
+            falls_into = last;
+        }
+
+        self.switch_depth -= 1;
+
+        if let (Ty::Enum(name), false) = (&subject_ty, covered.all || covered.has_errors) {
+            let missing = self.enums[name]
+                .variants
+                .iter()
+                .filter(|variant| !covered.variants.contains(&variant.name))
+                .map(|variant| format!("'{subject_ty}::{}'", variant.name))
+                .collect::<Vec<_>>();
+
+            self.error(
+                format!("switch on {subject_ty} doesn't cover {}, add arms for them or an 'else' arm", missing.join(", ")),
+                switch_stmt.span,
+            );
+        }
+
+        if covered.all {
+            self.exhaustive_switches.insert(switch_stmt);
+        }
+    }
+
+    /// Checks the alternatives of an arm and records what they cover. Returns the variables bound by the arm, which every
+    /// alternative has to bind with the same types.
+    fn check_arm_patterns(&mut self, arm: &SwitchArm, subject_ty: &Ty, covered: &mut SwitchCoverage) -> Vec<(String, Ty, Span)> {
+        let mut arm_bindings: Option<Vec<(String, Ty, Span)>> = None;
+
+        for pattern in &arm.cases {
+            let mut bindings = Vec::new();
+            let coverage = self.check_pattern(pattern, subject_ty, &mut bindings);
+
+            self.record_coverage(coverage, arm.guard.is_some(), subject_ty, pattern.span, covered);
+
+            let Some(first) = &arm_bindings else {
+                arm_bindings = Some(bindings);
+                continue;
+            };
+
+            let same_names = bindings.len() == first.len() && bindings.iter().all(|(name, ..)| first.iter().any(|(other, ..)| other == name));
+
+            if !same_names {
+                self.error("all alternatives of a switch arm must bind the same variables", pattern.span);
+                continue;
+            }
+
+            for (name, ty, span) in &bindings {
+                let (_, first_ty, _) = first.iter().find(|(other, ..)| other == name).unwrap();
+
+                if !first_ty.is_assignable_from(ty) || !ty.is_assignable_from(first_ty) {
+                    self.error(format!("mismatched types: '{name}' is {first_ty} in the first alternative, found {ty}"), *span);
+                }
+            }
+        }
+
+        arm_bindings.unwrap_or_default()
+    }
+
+    /// Reports patterns which can't match because earlier arms already cover their values. Guarded patterns may not
+    /// match, so they don't count as covering anything.
+    fn record_coverage(&mut self, coverage: Coverage, is_guarded: bool, subject_ty: &Ty, span: Span, covered: &mut SwitchCoverage) {
+        match coverage {
+            Coverage::All if !is_guarded => covered.all = true,
+            Coverage::Variant(name) | Coverage::PartOfVariant(name) if covered.variants.contains(&name) => {
+                self.error("unreachable pattern, its variant is already covered", span);
+            }
+            Coverage::Variant(name) if !is_guarded => {
+                covered.variants.push(name);
+
+                if let Ty::Enum(enum_name) = subject_ty {
+                    covered.all |= self.enums[enum_name].variants.iter().all(|variant| covered.variants.contains(&variant.name));
+                }
+            }
+            Coverage::Value(value) => {
+                let duplicate = covered.values.iter().any(|other| match (&value, other) {
+                    (CaseValue::Int(start, end), CaseValue::Int(other_start, other_end)) => start <= other_end && other_start <= end,
+                    _ => value == *other,
+                });
+
+                if duplicate {
+                    self.error("duplicate switch case", span);
+                }
+
+                if !is_guarded {
+                    covered.values.push(value);
+                }
+            }
+            Coverage::Error => covered.has_errors = true,
+            _ => {}
+        }
+    }
+
+    /// Checks a pattern against the type of the value it matches, collecting the variables it binds.
+    fn check_pattern(&mut self, pattern: &Pattern, ty: &Ty, bindings: &mut Vec<(String, Ty, Span)>) -> Coverage {
+        match &pattern.kind {
+            PatternKind::Wildcard => Coverage::All,
+            PatternKind::Ident(name) if self.scopes[1..].iter().all(|scope| !scope.contains_key(name)) && self.consts.contains_key(&self.qualify(self.current_module, name)) => {
+                let expr = Expr {
+                    kind: ExprKind::Ident(name.clone()),
+                    span: pattern.span,
+                };
+                self.check_const_pattern(&expr, ty)
+            }
+            PatternKind::Ident(name) => {
+                bindings.push((name.clone(), ty.clone(), pattern.span));
+                Coverage::All
+            }
+            PatternKind::Expr(expr) => self.check_const_pattern(expr, ty),
+            PatternKind::Range(range) => self.check_range_pattern(range, ty),
+            PatternKind::Variant(path, None) if matches!(self.split_module(&path.segments), (module, [name]) if self.consts.contains_key(&self.qualify(module, name))) => {
+                let expr = Expr {
+                    kind: ExprKind::Path(path.clone()),
+                    span: pattern.span,
+                };
+                self.check_const_pattern(&expr, ty)
+            }
+            PatternKind::Variant(path, args) => self.check_variant_pattern(path, args.as_deref(), ty, bindings, pattern.span),
+            PatternKind::Struct(path, fields, has_rest) => self.check_struct_pattern(path, fields, *has_rest, ty, bindings, pattern.span),
+        }
+    }
+
+    fn check_const_pattern(&mut self, expr: &Expr, ty: &Ty) -> Coverage {
+        let expr_ty = self.check_value(expr);
+
+        if !ty.is_assignable_from(&expr_ty) {
+            self.error(format!("mismatched types: expected {ty}, found {expr_ty}"), expr.span);
+            return Coverage::Error;
+        }
+
+        match self.eval_const_root(expr) {
+            Ok(ConstValue::Int(value)) => Coverage::Value(CaseValue::Int(value, value)),
+            Ok(ConstValue::Bool(value)) => Coverage::Value(CaseValue::Bool(value)),
+            Ok(ConstValue::Str(value)) => Coverage::Value(CaseValue::Str(value)),
+            _ => {
+                self.error("switch case must be a constant", expr.span);
+                Coverage::Error
+            }
+        }
+    }
+
+    fn check_range_pattern(&mut self, range: &Range, ty: &Ty) -> Coverage {
+        self.check_int(&range.start, "range bound");
+        self.check_int(&range.end, "range bound");
+
+        if !Ty::Int.is_assignable_from(ty) {
+            self.error(format!("range case requires an int subject, found {ty}"), range.start.span);
+            return Coverage::Error;
+        }
+
+        let (Some(start), Some(end)) = (self.const_int(&range.start), self.const_int(&range.end)) else {
+            self.error("switch case must be a constant", range.start.span);
+            return Coverage::Error;
+        };
+
+        let end = if range.inclusive { end } else { end - 1 };
+
+        if start > end {
+            self.error("empty case range", range.start.span);
+            return Coverage::Error;
+        }
+
+        Coverage::Value(CaseValue::Int(start, end))
+    }
+
+    /// Checks `Enum::Variant` or `Enum::Variant(a, b)` against the type of the matched value.
+    fn check_variant_pattern(&mut self, path: &Path, args: Option<&[Pattern]>, ty: &Ty, bindings: &mut Vec<(String, Ty, Span)>, span: Span) -> Coverage {
+        let args = args.unwrap_or_default();
+
+        let payload = match self.resolve_variant(path, span) {
+            Some((enum_name, variant)) if self.check_pattern_ty(ty, Ty::Enum(enum_name.clone()), span) => Some(variant),
+            _ => None,
+        };
+
+        let tys = match payload.as_ref().map(|variant| &variant.payload) {
+            Some(Payload::Unit) if args.is_empty() => Some(Vec::new()),
+            Some(Payload::Unit) => {
+                self.error(format!("variant '{path}' has no payload"), span);
+                None
+            }
+            Some(Payload::Tuple(tys)) if tys.len() == args.len() => Some(tys.clone()),
+            Some(Payload::Tuple(_)) if args.is_empty() => {
+                self.error(format!("variant '{path}' has a payload, match it with '{path}(...)'"), span);
+                None
+            }
+            Some(Payload::Tuple(tys)) => {
+                self.error(format!("variant '{path}' expects {} patterns, found {}", tys.len(), args.len()), span);
+                None
+            }
+            Some(Payload::Struct(_)) => {
+                self.error(format!("variant '{path}' has named fields, match it with '{path} {{ ... }}'"), span);
+                None
+            }
+            None => None,
+        };
+
+        let Some(tys) = tys else {
+            for arg in args {
+                self.check_pattern(arg, &Ty::Error, bindings);
+            }
+
+            return Coverage::Error;
+        };
+
+        let coverages = args.iter().zip(&tys).map(|(arg, ty)| self.check_pattern(arg, ty, bindings)).collect::<Vec<_>>();
+
+        Coverage::of_parts(coverages, payload.map(|variant| variant.name))
+    }
+
+    /// Checks `Name { ... }` for a struct, or `Enum::Variant { ... }` for a variant with named fields.
+    fn check_struct_pattern(&mut self, path: &Path, fields: &[FieldPattern], has_rest: bool, ty: &Ty, bindings: &mut Vec<(String, Ty, Span)>, span: Span) -> Coverage {
+        let (module, segments) = self.split_module(&path.segments);
+
+        let target = match segments {
+            [name] => {
+                let name = self.qualify(module, name);
+
+                self.check_visibility("struct", &name, &path.to_string(), span);
+
+                match self.structs.get(&name) {
+                    Some(info) => {
+                        // The subject's type arguments, or errors if it isn't this struct, which is reported below.
+                        let type_args = match ty {
+                            Ty::Struct(subject, type_args) if *subject == name => type_args.clone(),
+                            _ => vec![Ty::Error; info.type_params.len()],
+                        };
+
+                        let substitutions = info.type_params.iter().cloned().zip(type_args).collect::<HashMap<_, _>>();
+                        let infos = info
+                            .fields
+                            .iter()
+                            .map(|field| FieldInfo {
+                                ty: field.ty.substitute(&substitutions),
+                                ..field.clone()
+                            })
+                            .collect::<Vec<_>>();
+
+                        Some((format!("struct '{path}'"), infos, Ty::Struct(name, Vec::new()), None))
+                    }
+                    None => {
+                        self.error(format!("unknown struct '{path}'"), span);
+                        None
+                    }
+                }
+            }
+            _ => match self.resolve_variant(path, span) {
+                Some((
+                    enum_name,
+                    VariantInfo {
+                        name,
+                        payload: Payload::Struct(infos),
+                    },
+                )) => Some((format!("variant '{path}'"), infos, Ty::Enum(enum_name), Some(name))),
+                Some(_) => {
+                    self.error(format!("variant '{path}' has no named fields"), span);
+                    None
+                }
+                None => None,
+            },
+        };
+
+        let target = target.filter(|(_, _, found, _)| match (ty, found) {
+            (Ty::Struct(subject, _), Ty::Struct(name, _)) if subject == name => true,
+            _ => self.check_pattern_ty(ty, found.clone(), span),
+        });
+
+        let Some((owner, infos, found, variant)) = target else {
+            for field in fields {
+                self.check_pattern(&field.pattern, &Ty::Error, bindings);
+            }
+
+            return Coverage::Error;
+        };
+
+        let mut coverages = Vec::new();
+
+        for (i, field) in fields.iter().enumerate() {
+            if fields[..i].iter().any(|other| other.name == field.name) {
+                self.error(format!("field '{}' is specified more than once", field.name), field.span);
+                coverages.push(self.check_pattern(&field.pattern, &Ty::Error, bindings));
+                continue;
+            }
+
+            if let Ty::Struct(name, _) = &found {
+                self.check_visibility("field", &format!("{name}::{}", field.name), &field.name, field.span);
+            }
+
+            match infos.iter().find(|info| info.name == field.name) {
+                Some(info) => coverages.push(self.check_pattern(&field.pattern, &info.ty, bindings)),
+                None => {
+                    self.error(format!("{owner} has no field '{}'", field.name), field.span);
+                    self.check_pattern(&field.pattern, &Ty::Error, bindings);
+                    coverages.push(Coverage::Error);
+                }
+            }
+        }
+
+        let missing = infos
+            .iter()
+            .filter(|info| !fields.iter().any(|field| field.name == info.name))
+            .map(|info| format!("'{}'", info.name))
+            .collect::<Vec<_>>();
+
+        if !has_rest && !missing.is_empty() {
+            self.error(format!("missing fields {} in '{path}' pattern, use '..' to ignore them", missing.join(", ")), span);
+            coverages.push(Coverage::Error);
+        }
+
+        Coverage::of_parts(coverages, variant)
+    }
+
+    /// Whether a pattern of type `found` can match values of type `ty`, reporting a mismatch otherwise.
+    fn check_pattern_ty(&mut self, ty: &Ty, found: Ty, span: Span) -> bool {
+        if *ty == Ty::Error || *ty == found {
+            return true;
+        }
+
+        self.error(format!("mismatched types: expected {ty}, found {found}"), span);
+        false
+    }
+
+    /// Length of an array type or of a repeat literal, which has to be known at compile time.
+    fn array_len(&mut self, len: &Expr) -> Option<usize> {
+        match self.const_int(len) {
+            Some(len) if len >= 0 => Some(len as usize),
+            _ => {
+                self.error("array length must be a non-negative integer constant", len.span);
+                None
+            }
+        }
+    }
+
+    fn check_int(&mut self, expr: &Expr, what: &str) {
+        let ty = self.check_value(expr);
+
+        if !Ty::Int.is_assignable_from(&ty) {
+            self.error(format!("{what} must be int, found {ty}"), expr.span);
+        }
+    }
+
+    fn check_condition(&mut self, cond: &Expr) {
+        let ty = self.check_expr(cond);
+
+        if !Ty::Bool.is_assignable_from(&ty) {
+            self.error(format!("condition must be bool, found {ty}"), cond.span);
+        }
+    }
+
+    /// The binding of `if let` holds the unwrapped value and is only visible in the block.
+    fn check_if_let(&mut self, name: &str, cond: &Expr, then_block: &Block, span: Span) {
+        let ty = match self.check_value(cond) {
+            Ty::Optional(inner) => *inner,
+            Ty::Error => Ty::Error,
+            ty => {
+                self.error(format!("'if let' needs an optional value, found {ty}"), cond.span);
+                Ty::Error
+            }
+        };
+
+        self.scopes.push(HashMap::new());
+        self.define(name, ty, false, span);
+        self.check_block(then_block);
+        self.scopes.pop();
+    }
+
+    fn check_return(&mut self, return_stmt: &ReturnStmt) {
+        if self.in_defer {
+            self.error("'return' isn't allowed in deferred code", return_stmt.span);
+        }
+
+        let callee = match &self.current_fn {
+            Some(name) => format!("function '{name}'"),
+            None => "closure".to_string(),
+        };
+        let expected = self.return_ty.clone();
+
+        match &return_stmt.expr {
+            Some(expr) => {
+                let actual = self.check_expr(expr);
+
+                if expected == Ty::Void {
+                    self.error(format!("{callee} returns nothing, but a value of type {actual} is returned"), expr.span);
+                } else if !expected.is_assignable_from(&actual) {
+                    self.error(format!("mismatched return type: expected {expected}, found {actual}"), expr.span);
+                }
+            }
+            None => {
+                if expected != Ty::Void {
+                    self.error(format!("{callee} must return a value of type {expected}"), return_stmt.span);
+                }
+            }
+        }
+    }
+
+    /// Checks an expression whose result is used as a value, so it can't be void.
+    fn check_value(&mut self, expr: &Expr) -> Ty {
+        let ty = self.check_expr(expr);
+
+        if ty == Ty::Void {
+            self.error("expression doesn't produce a value", expr.span);
+            return Ty::Error;
+        }
+
+        ty
+    }
+
+    fn check_expr(&mut self, expr: &Expr) -> Ty {
+        match &expr.kind {
+            ExprKind::Int(_) => Ty::Int,
+            ExprKind::Float(_) => Ty::Float,
+            ExprKind::Str(_) => Ty::Str,
+            ExprKind::Bool(_) => Ty::Bool,
+            ExprKind::Nil => Ty::Nil,
+            ExprKind::Ident(name) => self.check_ident(name, expr.span),
+            ExprKind::Path(path) => self.check_path(path, expr.span),
+            ExprKind::Generic(path, type_args) => self.check_generic_fn(path, type_args, expr.span),
+            ExprKind::Unary(op, operand) => self.check_unary(*op, operand),
+            ExprKind::Update(op, operand) => self.check_update(*op, operand),
+            ExprKind::Binary(op, left, right) => self.check_binary(*op, left, right, expr.span),
+            ExprKind::Assign(op, target, value) => self.check_assign(*op, target, value, expr.span),
+            ExprKind::Ternary(cond, then_expr, else_expr) => {
+                self.check_condition(cond);
+
+                let then_ty = self.check_value(then_expr);
+                let else_ty = self.check_value(else_expr);
+
+                if !then_ty.is_assignable_from(&else_ty) {
+                    self.error(format!("ternary branches have different types: {then_ty} and {else_ty}"), expr.span);
+                    return Ty::Error;
+                }
+
+                then_ty
+            }
+            ExprKind::Call(callee, args) => self.check_call(callee, args),
+            ExprKind::Field(base, field) => self.check_field(base, field, expr.span),
+            ExprKind::OptionalField(base, field) => match self.check_optional_receiver(base) {
+                Ty::Error => Ty::Error,
+                base_ty => {
+                    let ty = self.check_field_value(&base_ty, field, expr.span);
+                    Self::optional_of(ty)
+                }
+            },
+            ExprKind::Coalesce(left, right) => self.check_coalesce(left, right),
+            ExprKind::Array(elems) => self.check_array(elems, expr.span),
+            ExprKind::Tuple(elems) => Ty::Tuple(elems.iter().map(|elem| self.check_value(elem)).collect()),
+            ExprKind::ArrayRepeat(value, count) => {
+                let ty = self.check_value(value);
+
+                match self.array_len(count) {
+                    Some(len) => Ty::Array(Box::new(ty), len),
+                    None => Ty::Error,
+                }
+            }
+            ExprKind::Index(base, index) => self.check_index(base, index),
+            ExprKind::Slice(base, range) => self.check_slice(base, range),
+            ExprKind::StructLit(path, fields) => self.check_struct_lit(path, fields, expr.span),
+            ExprKind::Closure(closure) => self.check_closure(closure),
+            ExprKind::Loop(loop_stmt) => self.check_loop_expr(loop_stmt),
+        }
+    }
+
+    /// The body is checked like a function body of its own, so `return` leaves the closure and loops of the enclosing
+    /// function can't be targeted. Captured variables are found through the enclosing scopes.
+    fn check_closure(&mut self, closure: &Closure) -> Ty {
+        let mut params = Vec::<Ty>::new();
+
+        for param in &closure.params {
+            if param.is_self() {
+                self.error("'self' parameter is only allowed in methods", param.span);
+            }
+
+            if let Some(default) = &param.default {
+                self.error("closure parameters can't have default values", default.span);
+            }
+
+            if param.is_variadic {
+                self.error("closure parameters can't be variadic", param.span);
+            }
+
+            params.push(self.resolve_param_type(param.type_info.as_ref()));
+        }
+
+        let declared = closure.return_type.as_ref().map(|type_info| self.resolve_type(type_info));
+
+        let current_fn = self.current_fn.take();
+        let return_ty = std::mem::replace(&mut self.return_ty, declared.clone().unwrap_or(Ty::Void));
+        let loops = std::mem::take(&mut self.loops);
+        let switch_depth = std::mem::replace(&mut self.switch_depth, 0);
+        let in_defer = std::mem::replace(&mut self.in_defer, false);
+
+        self.scopes.push(HashMap::new());
+
+        for (param, ty) in closure.params.iter().zip(&params) {
+            self.define(&param.name, ty.clone(), param.is_mutable, param.span);
+        }
+
+        let closure_ty = match &closure.body {
+            ClosureBody::Expr(expr) => self.check_expr(expr),
+            ClosureBody::Block(block) => {
+                for stmt in &block.stmts {
+                    self.check_stmt(stmt);
+                }
+
+                if self.return_ty != Ty::Void && !self.stmts_return(&block.stmts) {
+                    self.error(format!("missing return in closure returning {}", self.return_ty), block.span);
+                }
+
+                self.return_ty.clone()
+            }
+        };
+
+        self.scopes.pop();
+
+        self.current_fn = current_fn;
+        self.return_ty = return_ty;
+        self.loops = loops;
+        self.switch_depth = switch_depth;
+        self.in_defer = in_defer;
+
+        Ty::Fn(params, Box::new(closure_ty))
+    }
+
+    /// The elements all have the type of the first one.
+    fn check_array(&mut self, elems: &[Expr], span: Span) -> Ty {
+        let Some((first, rest)) = elems.split_first() else {
+            self.error("cannot infer the element type of an empty array", span);
+            return Ty::Error;
+        };
+
+        let elem_ty = self.check_value(first);
+
+        for elem in rest {
+            let ty = self.check_value(elem);
+
+            if !elem_ty.is_assignable_from(&ty) {
+                self.error(format!("mismatched types: expected {elem_ty}, found {ty}"), elem.span);
+            }
+        }
+
+        Ty::Array(Box::new(elem_ty), elems.len())
+    }
+
+    /// Indexes an array or a slice, possibly through a pointer. Constant indices are checked against the length of
+    /// arrays, and can't be negative for slices either.
+    fn check_index(&mut self, base: &Expr, index: &Expr) -> Ty {
+        let base_ty = self.check_receiver(base);
+        self.check_index_of(&base_ty, base.span, index)
+    }
+
+    fn check_index_of(&mut self, base_ty: &Ty, base_span: Span, index: &Expr) -> Ty {
+        self.check_int(index, "array index");
+
+        let (elem, len) = match base_ty {
+            Ty::Array(elem, len) => (elem.as_ref().clone(), Some(*len)),
+            Ty::Slice(elem) => (elem.as_ref().clone(), None),
+            Ty::Error => return Ty::Error,
+            _ => {
+                self.error(format!("cannot index value of type {base_ty}"), base_span);
+                return Ty::Error;
+            }
+        };
+
+        if let Some(value) = self.const_int(index) {
+            if value < 0 || len.is_some_and(|len| value >= len as i64) {
+                self.error(format!("index {value} is out of range for {base_ty}"), index.span);
+            }
+        }
+
+        elem
+    }
+
+    /// Slicing an array or a slice gives a slice. Constant bounds are checked against the length of arrays, where an
+    /// exclusive end can be the length itself.
+    fn check_slice(&mut self, base: &Expr, range: &Range) -> Ty {
+        let base_ty = self.check_receiver(base);
+        self.check_int(&range.start, "slice bound");
+        self.check_int(&range.end, "slice bound");
+
+        let (elem, len) = match &base_ty {
+            Ty::Array(elem, len) => (elem.as_ref().clone(), Some(*len as i64)),
+            Ty::Slice(elem) => (elem.as_ref().clone(), None),
+            Ty::Error => return Ty::Error,
+            _ => {
+                self.error(format!("cannot slice value of type {base_ty}"), base.span);
+                return Ty::Error;
+            }
+        };
+
+        let start = self.const_int(&range.start);
+        let end = self.const_int(&range.end);
+        let exclusive_end = end.map(|end| if range.inclusive { end + 1 } else { end });
+        let out_of_range = |bound: i64| bound < 0 || len.is_some_and(|len| bound > len);
+
+        if let Some(start) = start.filter(|start| out_of_range(*start)) {
+            self.error(format!("slice start {start} is out of range for {base_ty}"), range.start.span);
+        } else if let Some(end) = end.filter(|_| exclusive_end.is_some_and(out_of_range)) {
+            self.error(format!("slice end {end} is out of range for {base_ty}"), range.end.span);
+        } else if let (Some(start), Some(exclusive_end)) = (start, exclusive_end) {
+            if start > exclusive_end {
+                self.error(format!("slice start {start} is past its end {}", end.unwrap()), range.start.span);
+            }
+        }
+
+        Ty::Slice(Box::new(elem))
+    }
+
+    /// Checks the base of a field access or a method call, which sees through a pointer, so `p.x` is `p^.x`.
+    fn check_receiver(&mut self, base: &Expr) -> Ty {
+        match self.check_value(base) {
+            Ty::Pointer(pointee) => *pointee,
+            ty => ty,
+        }
+    }
+
+    /// Checks the base of `?.`, which has to be optional. The value it holds is seen through a pointer like with `.`.
+    fn check_optional_receiver(&mut self, base: &Expr) -> Ty {
+        match self.check_value(base) {
+            Ty::Optional(inner) => match *inner {
+                Ty::Pointer(pointee) => *pointee,
+                ty => ty,
+            },
+            Ty::Error => Ty::Error,
+            ty => {
+                self.error(format!("'?.' needs an optional value, found {ty}"), base.span);
+                Ty::Error
+            }
+        }
+    }
+
+    /// Result of `?.`, which is `nil` when the base is. An optional isn't wrapped again, `a?.b?.c` is a single `?T`.
+    fn optional_of(ty: Ty) -> Ty {
+        match ty {
+            Ty::Optional(_) | Ty::Void | Ty::Error => ty,
+            ty => Ty::Optional(Box::new(ty)),
+        }
+    }
+
+    /// The default of `??` is either a value of the wrapped type, which unwraps the optional, or another optional.
+    fn check_coalesce(&mut self, left: &Expr, right: &Expr) -> Ty {
+        let left_ty = self.check_value(left);
+        let right_ty = self.check_value(right);
+
+        match &left_ty {
+            Ty::Optional(inner) if inner.is_assignable_from(&right_ty) => *inner.clone(),
+            Ty::Optional(_) if left_ty.is_assignable_from(&right_ty) => left_ty,
+            Ty::Optional(inner) => {
+                self.error(format!("mismatched types: expected {inner}, found {right_ty}"), right.span);
+                Ty::Error
+            }
+            Ty::Error => Ty::Error,
+            _ => {
+                self.error(format!("left operand of '??' must be optional, found {left_ty}"), left.span);
+                Ty::Error
+            }
+        }
+    }
+
+    fn check_field(&mut self, base: &Expr, field: &str, span: Span) -> Ty {
+        let base_ty = self.check_receiver(base);
+        self.check_field_value(&base_ty, field, span)
+    }
+
+    /// A field read as a value, which can't name a method.
+    fn check_field_value(&mut self, base_ty: &Ty, field: &str, span: Span) -> Ty {
+        if let Ty::Struct(name, _) | Ty::Enum(name) = &base_ty {
+            if self.methods.contains_key(&(name.clone(), field.to_string())) {
+                self.error(format!("method '{name}::{field}' must be called"), span);
+                return Ty::Error;
+            }
+        }
+
+        self.check_field_of(base_ty, field, span)
+    }
+
+    fn check_field_of(&mut self, base_ty: &Ty, field: &str, span: Span) -> Ty {
+        if let Ty::Struct(name, _) = base_ty {
+            self.check_visibility("field", &format!("{name}::{field}"), field, span);
+        }
+
+        let field_ty = match &base_ty {
+            Ty::Struct(name, type_args) => self.structs.get(name).and_then(|info| {
+                let field = info.fields.iter().find(|info| info.name == field)?;
+                let bindings = info.type_params.iter().cloned().zip(type_args.iter().cloned()).collect();
+
+                Some(field.ty.substitute(&bindings))
+            }),
+            Ty::Tuple(elems) => field.parse::<usize>().ok().and_then(|index| elems.get(index).cloned()),
+            Ty::Optional(_) => {
+                self.error(format!("value of type {base_ty} may be nil, unwrap it with 'if let' or use '?.'"), span);
+                return Ty::Error;
+            }
+            Ty::Error => return Ty::Error,
+            _ => None,
+        };
+
+        field_ty.unwrap_or_else(|| {
+            self.error(format!("type {base_ty} has no field '{field}'"), span);
+            Ty::Error
+        })
+    }
+
+    /// Checks `Name { ... }` for a struct, or `Enum::Variant { ... }` for a variant with named fields. The type arguments
+    /// of a generic struct are inferred from the fields.
+    fn check_struct_lit(&mut self, path: &Path, inits: &[FieldInit], span: Span) -> Ty {
+        let (module, segments) = self.split_module(&path.segments);
+
+        let target = match segments {
+            [name] => {
+                let name = self.qualify(module, name);
+
+                self.check_visibility("struct", &name, &path.to_string(), span);
+
+                match self.structs.get(&name) {
+                    Some(info) => Some((format!("struct '{path}'"), info.fields.clone(), info.type_params.clone(), Ty::Struct(name, Vec::new()))),
+                    None => {
+                        self.error(format!("unknown struct '{path}'"), span);
+                        None
+                    }
+                }
+            }
+            _ => match self.resolve_variant(path, span) {
+                Some((enum_name, VariantInfo { payload: Payload::Struct(fields), .. })) => Some((format!("variant '{path}'"), fields, Vec::new(), Ty::Enum(enum_name))),
+                Some(_) => {
+                    self.error(format!("variant '{path}' has no named fields"), span);
+                    None
+                }
+                None => None,
+            },
+        };
+
+        let Some((owner, fields, type_params, ty)) = target else {
+            for init in inits {
+                self.check_expr(&init.expr);
+            }
+
+            return Ty::Error;
+        };
+
+        if let Ty::Struct(name, _) = &ty {
+            for init in inits {
+                self.check_visibility("field", &format!("{name}::{}", init.name), &init.name, init.span);
+            }
+        }
+
+        let type_args = self.check_field_inits(&owner, &path.to_string(), &fields, &type_params, inits, span);
+
+        match ty {
+            Ty::Struct(name, _) => Ty::Struct(name, type_args),
+            ty => ty,
+        }
+    }
+
+    /// Returns the type arguments inferred for `type_params`, the type parameters of a generic struct.
+    fn check_field_inits(&mut self, owner: &str, name: &str, fields: &[FieldInfo], type_params: &[String], inits: &[FieldInit], span: Span) -> Vec<Ty> {
+        let tys = inits.iter().map(|init| self.check_value(&init.expr)).collect::<Vec<_>>();
+        let mut bindings = HashMap::<String, Ty>::new();
+
+        for (init, ty) in inits.iter().zip(&tys) {
+            if let Some(field) = fields.iter().find(|field| field.name == init.name) {
+                field.ty.infer(ty, &mut bindings);
+            }
+        }
+
+        let type_args = self.inferred_type_args(owner, type_params, &mut bindings, span);
+
+        for (i, (init, ty)) in inits.iter().zip(&tys).enumerate() {
+            if inits[..i].iter().any(|other| other.name == init.name) {
+                self.error(format!("field '{}' is specified more than once", init.name), init.span);
+                continue;
+            }
+
+            match fields.iter().find(|field| field.name == init.name).map(|field| field.ty.substitute(&bindings)) {
+                Some(expected) if !expected.is_assignable_from(ty) => {
+                    self.error(format!("mismatched types: expected {expected}, found {ty}"), init.expr.span);
+                }
+                Some(_) => {}
+                None => self.error(format!("{owner} has no field '{}'", init.name), init.span),
+            }
+        }
+
+        let missing = fields
+            .iter()
+            .filter(|field| !field.has_default && !inits.iter().any(|init| init.name == field.name))
+            .map(|field| format!("'{}'", field.name))
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            self.error(format!("missing fields {} in '{name}' literal", missing.join(", ")), span);
+        }
+
+        type_args
+    }
+
+    /// A path is either an item of an imported module, or a unit variant. Variants with a payload have to be called or
+    /// built with a literal.
+    fn check_path(&mut self, path: &Path, span: Span) -> Ty {
+        if let (module, [name]) = self.split_module(&path.segments) {
+            let name = self.qualify(module, name);
+
+            if let Some(symbol) = self.scopes[0].get(&name) {
+                let ty = symbol.ty.clone();
+                self.check_visibility("global", &name, &path.to_string(), span);
+                return ty;
+            }
+
+            if let Some(sig) = self.functions.get(&name).cloned() {
+                self.check_visibility("function", &name, &path.to_string(), span);
+                return self.fn_value(&path.to_string(), &sig, &[], span);
+            }
+
+            self.error(format!("undeclared identifier '{path}'"), span);
+            return Ty::Error;
+        }
+
+        let Some((enum_name, variant)) = self.resolve_variant(path, span) else {
+            return Ty::Error;
+        };
+
+        match variant.payload {
+            Payload::Unit => Ty::Enum(enum_name),
+            Payload::Tuple(_) => {
+                self.error(format!("variant '{path}' has a payload, use '{path}(...)'"), span);
+                Ty::Error
+            }
+            Payload::Struct(_) => {
+                self.error(format!("variant '{path}' has named fields, use '{path} {{ ... }}'"), span);
+                Ty::Error
+            }
+        }
+    }
+
+    /// `max::<int>`, a generic function instantiated to be used as a value.
+    fn check_generic_fn(&mut self, path: &Path, type_args: &[TypeInfo], span: Span) -> Ty {
+        if let (module, [name]) = self.split_module(&path.segments) {
+            let name = self.qualify(module, name);
+
+            if let Some(sig) = self.functions.get(&name).cloned() {
+                self.check_visibility("function", &name, &path.to_string(), span);
+                return self.fn_value(&path.to_string(), &sig, type_args, span);
+            }
+        }
+
+        self.error(format!("undeclared function '{path}'"), span);
+        Ty::Error
+    }
+
+    /// Type of a named function used as a value. A generic function needs explicit type arguments, since there are no
+    /// arguments to infer them from.
+    fn fn_value(&mut self, name: &str, sig: &FnSig, type_args: &[TypeInfo], span: Span) -> Ty {
+        let bindings = if sig.type_params.is_empty() && type_args.is_empty() {
+            HashMap::new()
+        } else if type_args.is_empty() {
+            self.error(format!("generic function '{name}' can't be used as a value without type arguments"), span);
+            return Ty::Error;
+        } else {
+            match self.resolve_type_args(&format!("function '{name}'"), &sig.type_params, type_args, span) {
+                Some(type_args) => sig.type_params.iter().cloned().zip(type_args).collect(),
+                None => return Ty::Error,
+            }
+        };
+
+        self.check_bounds(&format!("function '{name}'"), &sig.bounds, &bindings, span);
+
+        let params = sig.params.iter().map(|param| param.substitute(&bindings)).collect();
+
+        Ty::Fn(params, Box::new(sig.return_ty.substitute(&bindings)))
+    }
+
+    /// Resolves `Enum::Variant` or `module::Enum::Variant`, reporting unknown enums and variants.
+    fn resolve_variant(&mut self, path: &Path, span: Span) -> Option<(String, VariantInfo)> {
+        let (module, [enum_name, variant_name]) = self.split_module(&path.segments) else {
+            self.error(format!("unknown path '{path}'"), span);
+            return None;
+        };
+
+        let enum_path = path.segments[..path.segments.len() - 1].join("::");
+        let qualified = self.qualify(module, enum_name);
+
+        if !self.enums.contains_key(&qualified) {
+            self.error(format!("unknown enum '{enum_path}'"), span);
+            return None;
+        }
+
+        self.check_visibility("enum", &qualified, &enum_path, span);
+
+        let info = &self.enums[&qualified];
+
+        match info.variants.iter().find(|variant| variant.name == *variant_name) {
+            Some(variant) => Some((qualified, variant.clone())),
+            None => {
+                self.error(format!("enum '{enum_path}' has no variant '{variant_name}'"), span);
+                None
+            }
+        }
+    }
+
+    fn check_variant_call(&mut self, path: &Path, type_args: &[TypeInfo], args: &[Arg], span: Span) -> Ty {
+        if !type_args.is_empty() {
+            self.error(format!("variant '{path}' is not generic"), span);
+        }
+
+        match self.resolve_variant(path, span) {
+            Some((enum_name, VariantInfo { payload: Payload::Tuple(types), .. })) => {
+                self.check_args(&format!("variant '{path}'"), &types, args, span);
+                Ty::Enum(enum_name)
+            }
+            Some(_) => {
+                self.error(format!("variant '{path}' can't be called"), span);
+                self.check_args_unchecked(args)
+            }
+            None => self.check_args_unchecked(args),
+        }
+    }
+
+    fn check_ident(&mut self, name: &str, span: Span) -> Ty {
+        if let Some(symbol) = self.lookup(name) {
+            return symbol.ty.clone();
+        }
+
+        if let Some(sig) = self.functions.get(&self.qualify(self.current_module, name)).cloned() {
+            return self.fn_value(name, &sig, &[], span);
+        }
+
+        self.error(format!("undeclared identifier '{name}'"), span);
+        Ty::Error
+    }
+
+    fn check_unary(&mut self, op: UnaryOp, operand: &Expr) -> Ty {
+        if op == UnaryOp::AddrOf {
+            return match self.check_place(operand) {
+                (Ty::Error, _) => Ty::Error,
+                (_, Access::Temporary(_)) => {
+                    self.error("cannot take the address of a temporary value", operand.span);
+                    Ty::Error
+                }
+                (ty, _) => Ty::Pointer(Box::new(ty)),
+            };
+        }
+
+        let ty = self.check_value(operand);
+
+        match op {
+            UnaryOp::Neg if ty.is_numeric() || ty == Ty::Error => ty,
+            UnaryOp::Not if Ty::Bool.is_assignable_from(&ty) => Ty::Bool,
+            UnaryOp::AddrOf => unreachable!(),
+            UnaryOp::Deref => match ty {
+                Ty::Pointer(pointee) => *pointee,
+                Ty::Error => Ty::Error,
+                ty => {
+                    self.error(format!("cannot dereference value of type {ty}"), operand.span);
+                    Ty::Error
+                }
+            },
+            UnaryOp::Neg => {
+                self.error(format!("cannot negate value of type {ty}"), operand.span);
+                Ty::Error
+            }
+            UnaryOp::Not => {
+                self.error(format!("cannot apply '!' to value of type {ty}"), operand.span);
+                Ty::Error
+            }
+        }
+    }
+
+    /// `++x` and `x++` both add one to a writable number, they only differ in the value they produce.
+    fn check_update(&mut self, op: UpdateOp, operand: &Expr) -> Ty {
+        let action = match op {
+            UpdateOp::PreInc | UpdateOp::PostInc => "increment",
+            UpdateOp::PreDec | UpdateOp::PostDec => "decrement",
+        };
+
+        let ty = self.check_assignable(operand, action);
+
+        if !ty.is_numeric() && ty != Ty::Error {
+            self.error(format!("cannot {action} value of type {ty}"), operand.span);
+            return Ty::Error;
+        }
+
+        ty
+    }
+
+    /// Checks an expression that is written to, `action` describing the write in diagnostics. The type is an error
+    /// if the expression can't be written.
+    fn check_assignable(&mut self, expr: &Expr, action: &str) -> Ty {
+        let (ty, access) = self.check_place(expr);
+
+        match access {
+            _ if ty == Ty::Error => Ty::Error,
+            Access::Mutable => ty,
+            Access::Immutable { name, module, span } => {
+                let note_file = (module > 0).then(|| self.modules[module].file.clone());
+                let diagnostic = Diagnostic::error(format!("cannot {action} '{name}', it isn't declared with 'var'"), expr.span).with_note(format!("'{name}' is declared here"), span, note_file);
+
+                self.report(diagnostic);
+                Ty::Error
+            }
+            Access::Temporary(what) => {
+                self.error(format!("cannot {action} {what}"), expr.span);
+                Ty::Error
+            }
+        }
+    }
+
+    /// Checks an expression that denotes a location rather than a temporary value: a variable, a field or an element of
+    /// a place, or a dereferenced pointer. A field of a temporary is a temporary, even through a pointer, `make()^.x` is
+    /// a place but `make().x` isn't. Whatever a pointer points to can be written, `p^.x` is mutable even if `p` isn't.
+    /// A temporary is classified before its value is checked, so `f()++` is rejected as a call even if `f` returns
+    /// nothing.
+    fn check_place(&mut self, expr: &Expr) -> (Ty, Access) {
+        match &expr.kind {
+            ExprKind::Ident(name) => {
+                let access = match self.lookup(name) {
+                    Some(symbol) if symbol.is_mutable => Access::Mutable,
+                    Some(symbol) => Access::Immutable {
+                        name: name.clone(),
+                        module: self.current_module,
+                        span: symbol.span,
+                    },
+                    None => Access::Temporary("a function"),
+                };
+
+                (self.check_ident(name, expr.span), access)
+            }
+            ExprKind::Path(path) => {
+                let access = match self.split_module(&path.segments) {
+                    (module, [name]) => match self.scopes[0].get(&self.qualify(module, name)) {
+                        Some(symbol) if symbol.is_mutable => Access::Mutable,
+                        Some(symbol) => Access::Immutable {
+                            name: path.to_string(),
+                            module,
+                            span: symbol.span,
+                        },
+                        None => Access::Temporary("a temporary value"),
+                    },
+                    _ => Access::Temporary("a temporary value"),
+                };
+
+                (self.check_path(path, expr.span), access)
+            }
+            ExprKind::Field(base, field) => {
+                let (base_ty, access) = self.check_place_base(base);
+                (self.check_field_value(&base_ty, field, expr.span), access)
+            }
+            ExprKind::Index(base, index) => {
+                let (base_ty, access) = self.check_place_base(base);
+                (self.check_index_of(&base_ty, base.span, index), access)
+            }
+            ExprKind::Unary(UnaryOp::Deref, _) => (self.check_value(expr), Access::Mutable),
+            ExprKind::Int(_) | ExprKind::Float(_) | ExprKind::Str(_) | ExprKind::Bool(_) | ExprKind::Nil => (self.check_expr(expr), Access::Temporary("a literal")),
+            ExprKind::Call(..) => (self.check_expr(expr), Access::Temporary("the result of a call")),
+            _ => (self.check_expr(expr), Access::Temporary("a temporary value")),
+        }
+    }
+
+    /// The base of a field or an element of a place, seen through a pointer like `check_receiver`.
+    fn check_place_base(&mut self, base: &Expr) -> (Ty, Access) {
+        match self.check_place(base) {
+            (Ty::Void, Access::Temporary(what)) => {
+                self.error("expression doesn't produce a value", base.span);
+                (Ty::Error, Access::Temporary(what))
+            }
+            (Ty::Pointer(pointee), Access::Temporary(what)) => (*pointee, Access::Temporary(what)),
+            (Ty::Pointer(pointee), _) => (*pointee, Access::Mutable),
+            place => place,
+        }
+    }
+
+    fn check_binary(&mut self, op: BinaryOp, left: &Expr, right: &Expr, span: Span) -> Ty {
+        let left_ty = self.check_value(left);
+        let right_ty = self.check_value(right);
+
+        self.check_binary_op(op, left_ty, right_ty, span)
+    }
+
+    fn check_binary_op(&mut self, op: BinaryOp, left_ty: Ty, right_ty: Ty, span: Span) -> Ty {
+        if left_ty == Ty::Error || right_ty == Ty::Error {
+            return match op {
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => Ty::Error,
+                _ => Ty::Bool,
+            };
+        }
+
+        let valid = match op {
+            BinaryOp::Add => left_ty == right_ty && (left_ty.is_numeric() || left_ty == Ty::Str),
+            BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => left_ty == right_ty && left_ty.is_numeric(),
+            BinaryOp::Less | BinaryOp::LessEq | BinaryOp::Greater | BinaryOp::GreaterEq => left_ty == right_ty && left_ty.is_numeric(),
+            BinaryOp::Eq | BinaryOp::NotEq => left_ty.is_assignable_from(&right_ty) || right_ty.is_assignable_from(&left_ty),
+            BinaryOp::And | BinaryOp::Or => left_ty == Ty::Bool && right_ty == Ty::Bool,
+        };
+
+        if !valid {
+            self.error(format!("invalid operands for binary operator: {left_ty} and {right_ty}"), span);
+        }
+
+        match op {
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div if valid => left_ty,
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => Ty::Error,
+            _ => Ty::Bool,
+        }
+    }
+
+    /// `x += v` is `x = x + v` with `x` evaluated once, so the operator has to be valid for the type of `x` and give
+    /// back that type.
+    fn check_assign(&mut self, op: Option<BinaryOp>, target: &Expr, value: &Expr, span: Span) -> Ty {
+        let target_ty = self.check_assignable(target, "assign to");
+        let value_ty = self.check_value(value);
+
+        match op {
+            Some(op) => {
+                if target_ty == Ty::Error {
+                    return Ty::Error;
+                }
+
+                self.check_binary_op(op, target_ty, value_ty, span)
+            }
+            None => {
+                if !target_ty.is_assignable_from(&value_ty) {
+                    self.error(format!("mismatched types: expected {target_ty}, found {value_ty}"), value.span);
+                }
+
+                target_ty
+            }
+        }
+    }
+
+    fn check_call(&mut self, callee: &Expr, args: &[Arg]) -> Ty {
+        match &callee.kind {
+            ExprKind::Ident(name) if self.lookup(name).is_none() => {
+                let path = Path { segments: vec![name.clone()] };
+                self.check_path_call(&path, &[], args, callee.span)
+            }
+            ExprKind::Path(path) => self.check_path_call(path, &[], args, callee.span),
+            ExprKind::Generic(path, type_args) => self.check_path_call(path, type_args, args, callee.span),
+            ExprKind::Field(base, method) => {
+                let base_ty = self.check_receiver(base);
+                self.check_method_call(base_ty, method, args, callee.span)
+            }
+            ExprKind::OptionalField(base, method) => match self.check_optional_receiver(base) {
+                Ty::Error => self.check_args_unchecked(args),
+                base_ty => {
+                    let ty = self.check_method_call(base_ty, method, args, callee.span);
+                    Self::optional_of(ty)
+                }
+            },
+            _ => {
+                let ty = self.check_expr(callee);
+                self.check_value_call(ty, args, callee.span)
+            }
+        }
+    }
+
+    /// `Point(1, 2)` runs the `init` constructor of `Point`. The type arguments of a generic struct are those of the
+    /// impl block declaring `init`.
+    fn check_constructor_call(&mut self, name: &str, type_args: &[TypeInfo], args: &[Arg], span: Span) -> Ty {
+        let Some(sig) = self.methods.get(&(name.to_string(), "init".to_string())).cloned() else {
+            self.error(format!("struct '{name}' has no 'init' constructor, use a '{name} {{ ... }}' literal"), span);
+            return self.check_args_unchecked(args);
+        };
+
+        let type_param_count = self.structs[name].type_params.len();
+        let struct_args = sig.type_params[..type_param_count].iter().map(|type_param| Ty::Param(type_param.clone())).collect();
+        let sig = FnSig {
+            return_ty: Ty::Struct(name.to_string(), struct_args),
+            ..sig
+        };
+
+        self.check_sig_call(&format!("constructor of '{name}'"), &sig, type_args, HashMap::new(), args, span)
+    }
+
+    /// `module::function(...)`, `Type::function(...)` or `Enum::Variant(...)`, where the type can also be qualified with
+    /// a module. Functions and variants of a type can't share a name.
+    fn check_path_call(&mut self, path: &Path, type_args: &[TypeInfo], args: &[Arg], span: Span) -> Ty {
+        let (module, segments) = self.split_module(&path.segments);
+
+        let sig = match segments {
+            [name] => {
+                let name = self.qualify(module, name);
+
+                if self.structs.contains_key(&name) {
+                    self.check_visibility("struct", &name, &path.to_string(), span);
+                    return self.check_constructor_call(&name, type_args, args, span);
+                }
+
+                self.check_visibility("function", &name, &path.to_string(), span);
+
+                match self.functions.get(&name).cloned() {
+                    Some(sig) => sig,
+                    None => {
+                        self.error(format!("undeclared function '{path}'"), span);
+                        return self.check_args_unchecked(args);
+                    }
+                }
+            }
+            [type_name, name] => {
+                let type_name = self.qualify(module, type_name);
+                let type_path = path.segments[..path.segments.len() - 1].join("::");
+
+                match self.methods.get(&(type_name.clone(), name.clone())).cloned() {
+                    Some(sig) => {
+                        let kind = if self.structs.contains_key(&type_name) { "struct" } else { "enum" };
+                        self.check_visibility(kind, &type_name, &type_path, span);
+                        self.check_visibility("method", &format!("{type_name}::{name}"), &path.to_string(), span);
+                        sig
+                    }
+                    None if self.structs.contains_key(&type_name) => {
+                        self.error(format!("type '{type_path}' has no function '{name}'"), span);
+                        return self.check_args_unchecked(args);
+                    }
+                    None => return self.check_variant_call(path, type_args, args, span),
+                }
+            }
+            _ => return self.check_variant_call(path, type_args, args, span),
+        };
+
+        if sig.has_self {
+            self.error(format!("method '{path}' takes 'self' and must be called on a value"), span);
+            return self.check_args_unchecked(args);
+        }
+
+        self.check_sig_call(&format!("function '{path}'"), &sig, type_args, HashMap::new(), args, span)
+    }
+
+    /// Checks `value.method(...)` given the type of `value`, already seen through a pointer. Without such a method, the callee is a field holding a function value.
+    fn check_method_call(&mut self, base_ty: Ty, method: &str, args: &[Arg], span: Span) -> Ty {
+        let (type_name, type_args) = match &base_ty {
+            Ty::Struct(name, type_args) => (name.clone(), type_args.clone()),
+            Ty::Enum(name) => (name.clone(), Vec::new()),
+            Ty::Param(name) => return self.check_bound_method_call(&base_ty, &name.clone(), method, args, span),
+            Ty::Error => return self.check_args_unchecked(args),
+            _ => (String::new(), Vec::new()),
+        };
+
+        let Some(sig) = self.methods.get(&(type_name.clone(), method.to_string())).cloned() else {
+            let ty = self.check_field_of(&base_ty, method, span);
+            return self.check_value_call(ty, args, span);
+        };
+
+        if !sig.has_self {
+            self.error(format!("'{type_name}::{method}' doesn't take 'self', call it as '{type_name}::{method}(...)'"), span);
+            return self.check_args_unchecked(args);
+        }
+
+        self.check_visibility("method", &format!("{type_name}::{method}"), method, span);
+
+        let bindings = sig.type_params.iter().cloned().zip(type_args).collect();
+
+        self.check_sig_call(&format!("method '{type_name}::{method}'"), &sig, &[], bindings, args, span)
+    }
+
+    /// `value.method(...)` on a value of a type parameter, resolved through the interfaces bounding it. The method of
+    /// the type argument is called, so `Self` is the type parameter.
+    fn check_bound_method_call(&mut self, base_ty: &Ty, type_param: &str, method: &str, args: &[Arg], span: Span) -> Ty {
+        let found = self
+            .bounds
+            .iter()
+            .filter(|(param, _)| param == type_param)
+            .filter_map(|(_, interface)| {
+                let (_, sig) = self.interfaces[interface].methods.iter().find(|(name, _)| name == method)?;
+                Some((interface.clone(), sig.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        match &found[..] {
+            [] => {
+                let ty = self.check_field_of(base_ty, method, span);
+                self.check_value_call(ty, args, span)
+            }
+            [(interface, sig)] => {
+                let bindings = HashMap::from([("Self".to_string(), base_ty.clone())]);
+                self.check_sig_call(&format!("method '{interface}::{method}'"), sig, &[], bindings, args, span)
+            }
+            [(first, _), (second, _), ..] => {
+                self.error(format!("method '{method}' of '{type_param}' is ambiguous, interfaces '{first}' and '{second}' both declare it"), span);
+                self.check_args_unchecked(args)
+            }
+        }
+    }
+
+    /// Calls a value of function type, such as a closure or a function passed as an argument.
+    fn check_value_call(&mut self, ty: Ty, args: &[Arg], span: Span) -> Ty {
+        match ty {
+            Ty::Fn(params, return_ty) => {
+                let callee = format!("value of type {}", Ty::Fn(params.clone(), return_ty.clone()));
+                self.check_args(&callee, &params, args, span);
+                *return_ty
+            }
+            Ty::Error => self.check_args_unchecked(args),
+            ty => {
+                self.error(format!("value of type {ty} is not callable"), span);
+                self.check_args_unchecked(args)
+            }
+        }
+    }
+
+    /// Checks a call to a function, a method or a constructor. The type arguments of a generic one are inferred from
+    /// the arguments unless they're given explicitly, except for those already bound by the receiver of a method.
+    fn check_sig_call(&mut self, callee: &str, sig: &FnSig, type_args: &[TypeInfo], mut bindings: HashMap<String, Ty>, args: &[Arg], span: Span) -> Ty {
+        let free = sig.type_params.iter().filter(|type_param| !bindings.contains_key(*type_param)).cloned().collect::<Vec<_>>();
+
+        if !type_args.is_empty() {
+            if let Some(type_args) = self.resolve_type_args(callee, &free, type_args, span) {
+                bindings.extend(free.iter().cloned().zip(type_args));
+            }
+        }
+
+        let slots = self.bind_args(callee, sig, args, span);
+        let arg_tys = self.check_arg_values(&slots, args);
+
+        for ((arg, slot), arg_ty) in args.iter().zip(&slots).zip(&arg_tys) {
+            if let Some(param) = slot {
+                Self::param_ty(&sig.params, sig.variadic, *param, arg).infer(arg_ty, &mut bindings);
+            }
+        }
+
+        self.inferred_type_args(callee, &free, &mut bindings, span);
+        self.check_bounds(callee, &sig.bounds, &bindings, span);
+
+        let params = sig.params.iter().map(|param| param.substitute(&bindings)).collect::<Vec<_>>();
+        self.match_args(&params, sig.variadic, args, &slots, &arg_tys);
+
+        sig.return_ty.substitute(&bindings)
+    }
+
+    /// Reports type arguments not implementing the interfaces their type parameter is bounded by.
+    fn check_bounds(&mut self, item: &str, bounds: &[(String, String)], bindings: &HashMap<String, Ty>, span: Span) {
+        for (type_param, interface) in bounds {
+            let ty = &bindings[type_param];
+
+            if !self.implements(ty, interface) {
+                self.error(format!("type {ty} doesn't implement interface '{interface}', required by '{type_param}' of {item}"), span);
+            }
+        }
+    }
+
+    /// Whether a type implements an interface. A type parameter does if it's bounded by the interface.
+    fn implements(&self, ty: &Ty, interface: &str) -> bool {
+        match ty {
+            Ty::Struct(name, _) | Ty::Enum(name) => self.interface_impls.contains_key(&(name.clone(), interface.to_string())),
+            Ty::Param(name) => self.bounds.iter().any(|(param, bound)| param == name && bound == interface),
+            Ty::Error => true,
+            _ => false,
+        }
+    }
+
+    /// Resolves explicit type arguments, which have to match the type parameters in number.
+    fn resolve_type_args(&mut self, item: &str, type_params: &[String], type_args: &[TypeInfo], span: Span) -> Option<Vec<Ty>> {
+        let type_args = type_args.iter().map(|type_info| self.resolve_type(type_info)).collect::<Vec<_>>();
+
+        if type_params.is_empty() {
+            self.error(format!("{item} is not generic"), span);
+            return None;
+        }
+
+        if type_args.len() != type_params.len() {
+            self.error(format!("{item} expects {} type arguments, found {}", type_params.len(), type_args.len()), span);
+            return None;
+        }
+
+        Some(type_args)
+    }
+
+    /// Collects the inferred type arguments, reporting the type parameters nothing could be inferred for.
+    fn inferred_type_args(&mut self, item: &str, type_params: &[String], bindings: &mut HashMap<String, Ty>, span: Span) -> Vec<Ty> {
+        let mut type_args = Vec::<Ty>::new();
+
+        for type_param in type_params {
+            if !bindings.contains_key(type_param) {
+                self.error(format!("cannot infer type argument '{type_param}' of {item}"), span);
+                bindings.insert(type_param.clone(), Ty::Error);
+            }
+
+            type_args.push(bindings[type_param].clone());
+        }
+
+        type_args
+    }
+
+    /// Checks call arguments against the parameter types, `callee` describing what's called in diagnostics. Values and
+    /// variants only take positional arguments.
+    fn check_args(&mut self, callee: &str, params: &[Ty], args: &[Arg], span: Span) {
+        for arg in args {
+            if let Some(name) = &arg.name {
+                self.error(format!("{callee} can't take named arguments, found '{name}'"), arg.span);
+            } else if arg.spread {
+                self.error(format!("{callee} has no variadic parameter to spread into"), arg.span);
+            }
+        }
+
+        let slots = args.iter().enumerate().map(|(i, arg)| (i < params.len() && !arg.spread).then_some(i)).collect::<Vec<_>>();
+        let arg_tys = self.check_arg_values(&slots, args);
+
+        if params.len() != args.len() {
+            self.error(format!("{callee} expects {} arguments, found {}", params.len(), args.len()), span);
+        }
+
+        self.match_args(params, false, args, &slots, &arg_tys);
+    }
+
+    /// Matches the arguments of a call to a function, a method or a constructor with its parameters: positional ones
+    /// in order, then named ones by name, the positional ones left over going to a variadic parameter. Returns the
+    /// index of the parameter for each argument, `None` for those matching none.
+    fn bind_args(&mut self, callee: &str, sig: &FnSig, args: &[Arg], span: Span) -> Vec<Option<usize>> {
+        let total = sig.params.len();
+        let fixed = total - usize::from(sig.variadic);
+        let positional = args.iter().take_while(|arg| arg.name.is_none()).count();
+
+        let mut slots = vec![None; args.len()];
+        let mut given = vec![false; total];
+        let mut bound = true;
+
+        for (i, arg) in args.iter().enumerate() {
+            let error = match &arg.name {
+                None if i >= positional => "positional argument can't follow a named argument".to_string(),
+                None if arg.spread && !sig.variadic => format!("{callee} has no variadic parameter to spread into"),
+                None if arg.spread && (i != fixed || positional != total) => {
+                    format!("spread argument must be the only argument for variadic parameter '{}'", sig.param_names[fixed])
+                }
+                None => {
+                    let param = if sig.variadic { i.min(fixed) } else { i };
+
+                    if param < total {
+                        slots[i] = Some(param);
+                        given[param] = true;
+                    }
+                    continue;
+                }
+                Some(name) => match sig.param_names.iter().position(|param| param == name) {
+                    Some(param) if sig.variadic && param == fixed => format!("variadic parameter '{name}' can't be given by name"),
+                    Some(param) if given[param] => format!("argument for parameter '{name}' is given more than once"),
+                    Some(param) => {
+                        slots[i] = Some(param);
+                        given[param] = true;
+                        continue;
+                    }
+                    None => format!("{callee} has no parameter '{name}'"),
+                },
+            };
+
+            self.error(error, arg.span);
+            bound = false;
+        }
+
+        let required = sig.has_default[..fixed].iter().filter(|has_default| !**has_default).count();
+
+        let missing = (0..fixed)
+            .filter(|param| !given[*param] && !sig.has_default[*param])
+            .map(|param| format!("'{}'", sig.param_names[param]))
+            .collect::<Vec<_>>();
+
+        if (!sig.variadic && positional > total) || (bound && positional == args.len() && !missing.is_empty()) {
+            let expected = match (required == total, positional > total) {
+                (true, _) => total.to_string(),
+                (false, true) => format!("at most {total}"),
+                (false, false) => format!("at least {required}"),
+            };
+
+            self.error(format!("{callee} expects {expected} arguments, found {positional}"), span);
+        } else if bound && !missing.is_empty() {
+            self.error(format!("missing arguments {} in call to {callee}", missing.join(", ")), span);
+        }
+
+        slots
+    }
+
+    /// Checks the arguments of a call, those not matching a parameter only for errors of their own.
+    fn check_arg_values(&mut self, slots: &[Option<usize>], args: &[Arg]) -> Vec<Ty> {
+        args.iter()
+            .zip(slots)
+            .map(|(arg, slot)| if slot.is_some() { self.check_value(&arg.value) } else { self.check_expr(&arg.value) })
+            .collect()
+    }
+
+    fn match_args(&mut self, params: &[Ty], variadic: bool, args: &[Arg], slots: &[Option<usize>], arg_tys: &[Ty]) {
+        for ((arg, slot), arg_ty) in args.iter().zip(slots).zip(arg_tys) {
+            let Some(param) = *slot else {
+                continue;
+            };
+
+            let param = Self::param_ty(params, variadic, param, arg);
+
+            if !param.is_assignable_from(arg_ty) {
+                self.error(format!("mismatched types: expected {param}, found {arg_ty}"), arg.value.span);
+            }
+        }
+    }
+
+    /// Type an argument for the parameter at `param` must have, the element type for each argument given to a
+    /// variadic parameter unless it spreads a slice.
+    fn param_ty<'t>(params: &'t [Ty], variadic: bool, param: usize, arg: &Arg) -> &'t Ty {
+        match &params[param] {
+            Ty::Slice(elem) if variadic && param + 1 == params.len() && !arg.spread => elem,
+            ty => ty,
+        }
+    }
+
+    /// Checks the arguments of a call that already failed, so errors inside them are still reported.
+    fn check_args_unchecked(&mut self, args: &[Arg]) -> Ty {
+        for arg in args {
+            self.check_expr(&arg.value);
+        }
+
+        Ty::Error
+    }
+
+    fn resolve_param_type(&mut self, type_info: Option<&TypeInfo>) -> Ty {
+        match type_info {
+            Some(type_info) => self.resolve_type(type_info),
+            None => Ty::Int,
+        }
+    }
+
+    fn resolve_type(&mut self, type_info: &TypeInfo) -> Ty {
+        match &type_info.kind {
+            TypeKind::Base(name) => match name.as_str() {
+                "int" => Ty::Int,
+                "float" => Ty::Float,
+                "bool" => Ty::Bool,
+                "string" => Ty::Str,
+                "any" => Ty::Any,
+                _ if self.type_params.contains(name) => Ty::Param(name.clone()),
+                _ => self.resolve_type_path(&Path { segments: vec![name.clone()] }, &[], type_info.span),
+            },
+            TypeKind::Path(path) => self.resolve_type_path(path, &[], type_info.span),
+            TypeKind::Generic(path, type_args) => self.resolve_type_path(path, type_args, type_info.span),
+            TypeKind::Array(elem, len) => {
+                let elem = self.resolve_type(elem);
+
+                match self.array_len(len) {
+                    Some(len) => Ty::Array(Box::new(elem), len),
+                    None => Ty::Error,
+                }
+            }
+            TypeKind::Slice(elem) => Ty::Slice(Box::new(self.resolve_type(elem))),
+            TypeKind::Pointer(pointee) => Ty::Pointer(Box::new(self.resolve_type(pointee))),
+            TypeKind::Optional(inner) => Ty::Optional(Box::new(self.resolve_type(inner))),
+            TypeKind::Tuple(elems) => Ty::Tuple(elems.iter().map(|elem| self.resolve_type(elem)).collect()),
+            TypeKind::Fn(params, return_type) => {
+                let params = params.iter().map(|param| self.resolve_type(param)).collect();
+                let return_ty = match return_type {
+                    Some(return_type) => self.resolve_type(return_type),
+                    None => Ty::Void,
+                };
+
+                Ty::Fn(params, Box::new(return_ty))
+            }
+        }
+    }
+
+    /// Resolves a struct or enum, possibly from an imported module. Generic structs need all their type arguments.
+    fn resolve_type_path(&mut self, path: &Path, type_args: &[TypeInfo], span: Span) -> Ty {
+        if let (module, [name]) = self.split_module(&path.segments) {
+            let name = self.qualify(module, name);
+
+            if let Some(info) = self.structs.get(&name) {
+                let type_params = info.type_params.clone();
+
+                self.check_visibility("struct", &name, &path.to_string(), span);
+
+                if type_params.is_empty() && type_args.is_empty() {
+                    return Ty::Struct(name, Vec::new());
+                }
+
+                return match self.resolve_type_args(&format!("struct '{path}'"), &type_params, type_args, span) {
+                    Some(type_args) => Ty::Struct(name, type_args),
+                    None => Ty::Error,
+                };
+            }
+
+            if self.enums.contains_key(&name) {
+                self.check_visibility("enum", &name, &path.to_string(), span);
+
+                if !type_args.is_empty() {
+                    self.error(format!("enum '{path}' is not generic"), span);
+                    return Ty::Error;
+                }
+
+                return Ty::Enum(name);
+            }
+
+            if self.interfaces.contains_key(&name) {
+                self.error(format!("interface '{path}' can't be used as a type, use a type parameter bounded by it"), span);
+                return Ty::Error;
+            }
+        }
+
+        self.error(format!("unknown type '{path}'"), span);
+        Ty::Error
+    }
+
+    /// Globals are stored qualified with their module, like other items.
+    fn define(&mut self, name: &str, ty: Ty, is_mutable: bool, span: Span) {
+        let key = if self.scopes.len() == 1 { self.qualify(self.current_module, name) } else { name.to_string() };
+        let scope = self.scopes.last_mut().unwrap();
+
+        if scope.contains_key(&key) {
+            self.error(format!("'{name}' is already declared in this scope"), span);
+            return;
+        }
+
+        scope.insert(key, Symbol { ty, is_mutable, span });
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Symbol> {
+        let global = self.qualify(self.current_module, name);
+
+        self.scopes[1..].iter().rev().find_map(|scope| scope.get(name)).or_else(|| self.scopes[0].get(&global))
+    }
+
+    fn error(&mut self, message: impl Into<String>, span: Span) {
+        self.report(Diagnostic::error(message, span));
+    }
+
+    /// Diagnostics in imported modules point into their file.
+    fn report(&mut self, mut diagnostic: Diagnostic) {
+        if self.current_module > 0 {
+            diagnostic = diagnostic.in_file(self.modules[self.current_module].file.clone());
+        }
+
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Whether every path through the statements ends with `return` or never falls through.
+    fn stmts_return(&self, stmts: &[Stmt]) -> bool {
+        stmts.iter().any(|stmt| self.stmt_returns(stmt))
+    }
+
+    fn stmt_returns(&self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Return(_) => true,
+            Stmt::Block(block) => self.stmts_return(&block.stmts),
+            Stmt::Attributed(attributed) => self.stmt_returns(&attributed.stmt),
+            Stmt::If(if_stmt) => match &if_stmt.else_branch {
+                Some(else_branch) => self.stmts_return(&if_stmt.then_block.stmts) && self.stmt_returns(else_branch),
+                None => false,
+            },
+            Stmt::Loop(loop_stmt) => !Self::stmts_break(&loop_stmt.body.stmts, &[]),
+            Stmt::Switch(switch_stmt) => {
+                let is_exhaustive = self.exhaustive_switches.contains(&(switch_stmt as *const SwitchStmt));
+                let mut next_returns = false;
+
+                // An arm ending with `fall` returns if the arm it falls into does.
+                let all_return = switch_stmt.arms.iter().rev().all(|arm| {
+                    let falls = matches!(arm.body.stmts.last(), Some(Stmt::Fall(_)));
+                    next_returns = self.stmts_return(&arm.body.stmts) || (falls && next_returns);
+                    next_returns
+                });
+
+                is_exhaustive && all_return
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the statements contain a `break` that leaves the enclosing loop: an unlabeled one outside of nested
+    /// loops, or one whose label isn't that of a nested loop. `nested` holds the labels of the loops entered so far.
+    fn stmts_break(stmts: &[Stmt], nested: &[Option<&str>]) -> bool {
+        stmts.iter().any(|stmt| match stmt {
+            Stmt::Break(break_stmt) => match &break_stmt.target {
+                BreakTarget::Name(name, _) => !nested.contains(&Some(name.as_str())),
+                _ => nested.is_empty(),
+            },
+            Stmt::Block(block) => Self::stmts_break(&block.stmts, nested),
+            Stmt::Attributed(attributed) => Self::stmts_break(std::slice::from_ref(&*attributed.stmt), nested),
+            Stmt::If(if_stmt) => Self::stmts_break(&if_stmt.then_block.stmts, nested) || if_stmt.else_branch.as_deref().is_some_and(|stmt| Self::stmts_break(std::slice::from_ref(stmt), nested)),
+            Stmt::Switch(switch_stmt) => switch_stmt.arms.iter().any(|arm| Self::stmts_break(&arm.body.stmts, nested)),
+            Stmt::While(while_stmt) => Self::stmts_break(&while_stmt.body.stmts, &[nested, &[while_stmt.label.as_deref()]].concat()),
+            Stmt::Loop(loop_stmt) => Self::stmts_break(&loop_stmt.body.stmts, &[nested, &[loop_stmt.label.as_deref()]].concat()),
+            Stmt::For(for_stmt) => Self::stmts_break(&for_stmt.body.stmts, &[nested, &[for_stmt.label.as_deref()]].concat()),
+            _ => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::lexer::Lexer;
+    use crate::compiler::parser::decl_parser::DeclParser;
+    use crate::compiler::token_stream::TokenStream;
+
+    use super::*;
+
+    fn check(source: &str) -> Vec<String> {
+        check_modules(&[("", source)])
+    }
+
+    /// Checks a root module and modules it imports, each given by name and source. Every module imports the modules
+    /// listed after it. Messages are prefixed with the file of imported modules and followed by their notes.
+    fn check_modules(sources: &[(&str, &str)]) -> Vec<String> {
+        let modules = sources
+            .iter()
+            .enumerate()
+            .map(|(i, (name, source))| {
+                let tokens = Lexer::new("/test.mv", source).tokenize();
+                let mut stream = TokenStream::new(tokens);
+                let decls = DeclParser::new(&mut stream).parse();
+                assert!(stream.take_diagnostics().is_empty());
+
+                Module {
+                    name: name.to_string(),
+                    file: format!("{name}.mv"),
+                    decls,
+                    imports: sources.iter().enumerate().skip(i + 1).map(|(j, (name, _))| (name.to_string(), j)).collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Checker::new()
+            .check(&modules)
+            .into_iter()
+            .map(|diagnostic| {
+                let message = match diagnostic.file {
+                    Some(file) => format!("{file}: {}", diagnostic.message),
+                    None => diagnostic.message,
+                };
+
+                diagnostic.notes.iter().fold(message, |message, note| {
+                    let file = note.file.as_deref().unwrap_or("");
+                    format!("{message} (note: {} at {file}:{}:{})", note.message, note.span.line, note.span.column)
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn return_matches_declared_type() {
+        let errors = check("fn add(let a: int, let b: int) -> int { return a + b; } fn main() { let x: int = add(1, 2); }");
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn return_type_mismatch() {
+        let errors = check("fn half(let a: int) -> float { return a; }");
+
+        assert_eq!(errors, vec!["mismatched return type: expected float, found int"]);
+    }
+
+    #[test]
+    fn return_value_from_fn_without_return_type() {
+        let errors = check("fn foo() { return 1; }");
+
+        assert_eq!(errors, vec!["function 'foo' returns nothing, but a value of type int is returned"]);
+    }
+
+    #[test]
+    fn missing_return_value() {
+        let errors = check("fn foo() -> int { if true { return 1; } }");
+
+        assert_eq!(errors, vec!["missing return in function 'foo' returning int"]);
+    }
+
+    #[test]
+    fn all_paths_return() {
+        let errors = check("fn sign(let a: int) -> int { if a < 0 { return -1; } else if a > 0 { return 1; } else { return 0; } }");
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn void_fn_result_used_as_value() {
+        let errors = check("fn foo() {} fn bar() { let x := foo(); }");
+
+        assert_eq!(errors, vec!["expression doesn't produce a value"]);
+    }
+
+    #[test]
+    fn for_over_ranges_and_collections() {
+        let errors = check("fn sum(let xs: [int], let fixed: [float; 3]) { for i in 0..10 step 2 { let j: int = i; } for x in xs { let y: int = x; } for f in fixed { let g: float = f; } }");
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn for_range_bounds_must_be_int() {
+        let errors = check("fn foo() { for i in 0..1.5 step 0 {} }");
+
+        assert_eq!(errors, vec!["range bound must be int, found float", "loop step can't be zero"]);
+    }
+
+    #[test]
+    fn constant_zero_step() {
+        let errors = check("const Z: int = 0; fn foo(let n: int) { for i in 0..10 step Z {} for i in 0..10 step 1 - 1 {} for i in 0..10 step n - n {} }");
+
+        assert_eq!(errors, vec!["loop step can't be zero"; 2]);
+    }
+
+    #[test]
+    fn for_over_non_collection() {
+        let errors = check("fn foo(let n: int) { for x in n {} }");
+
+        assert_eq!(errors, vec!["cannot iterate over value of type int"]);
+    }
+
+    #[test]
+    fn for_variable_is_scoped_to_body() {
+        let errors = check("fn foo() { for i in 0..10 { let i := 1; } print i; }");
+
+        assert_eq!(errors, vec!["'i' is already declared in this scope", "undeclared identifier 'i'"]);
+    }
+
+    #[test]
+    fn switch_with_fall() {
+        let errors = check("fn foo(let x: int) -> int { switch x { 1, 2 -> fall; -3..=0 -> return 0; else -> return 1; } }");
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn switch_fall_in_last_arm() {
+        let errors = check("fn foo(let x: int) { switch x { 1 -> print 1; else -> { print 2; fall; } } }");
+
+        assert_eq!(errors, vec!["'fall' in the last switch arm has no arm to fall into"]);
+    }
+
+    #[test]
+    fn switch_fall_must_end_arm() {
+        let errors = check("fn foo(let x: int) { switch x { 1 -> { fall; print 1; } else -> print 2; } fall; }");
+
+        assert_eq!(errors, vec!["'fall' must be the last statement of a switch arm", "'fall' outside of a switch"]);
+    }
+
+    #[test]
+    fn switch_duplicate_cases() {
+        let errors = check("fn foo(let x: int, let s: string) { switch x { 1, 2 -> print 1; 2 -> print 2; 5..10 -> print 3; 9..=12 -> print 4; } switch s { \"a\" -> print 1; \"a\" -> print 2; } }");
+
+        assert_eq!(errors, vec!["duplicate switch case", "duplicate switch case", "duplicate switch case"]);
+    }
+
+    #[test]
+    fn switch_cases_must_be_constants_of_subject_type() {
+        let errors = check("fn foo(let x: int, let y: int) { switch x { y + 1 -> print 1; true -> print 2; } }");
+
+        assert_eq!(errors, vec!["switch case must be a constant", "mismatched types: expected int, found bool"]);
+    }
+
+    #[test]
+    fn struct_literals_and_field_access() {
+        let errors = check("struct Point { x: int = 0, y: int } struct Line { from: Point, to: Point } fn len(let l: Line) -> int { let p := Point { y: 2 }; return l.to.x - l.from.x + p.y; }");
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn struct_literal_fields_are_checked() {
+        let errors = check("struct Point { x: int, y: int, z: int = 0 } fn foo() { let p := Point { x: 1.5, x: 2, w: 3 }; print p.w; }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "mismatched types: expected int, found float",
+                "field 'x' is specified more than once",
+                "struct 'Point' has no field 'w'",
+                "missing fields 'y' in 'Point' literal",
+                "type Point has no field 'w'",
+            ]
+        );
+    }
+
+    #[test]
+    fn struct_declarations_are_checked() {
+        let errors = check("struct A { x: int, x: float, y: B = 1 } struct A {}");
+
+        assert_eq!(errors, vec!["struct 'A' is already declared", "field 'x' is already declared in struct 'A'", "unknown type 'B'"]);
+    }
+
+    #[test]
+    fn enum_variants_construct_enum_values() {
+        let errors = check(
+            "enum Color { Red = 1, Green, Blue = 5 } enum Msg { Move { x: int, y: int }, Write(string), Quit } \
+             fn foo() -> Msg { let c: Color = Color::Green; let w := Msg::Write(\"hi\"); let q: Msg = Msg::Quit; if c == Color::Red { return w; } return Msg::Move { x: 1, y: 2 }; }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn enum_declarations_are_checked() {
+        let errors = check("enum Color { Red = 1, Green, Red, Blue = 2 } enum Opt { Some(int) = 1, None } struct Color {}");
+
+        assert_eq!(
+            errors,
+            vec![
+                "enum 'Color' is already declared",
+                "variant 'Red' is already declared in enum 'Color'",
+                "discriminant value 2 is already used by variant 'Green'",
+                "explicit discriminants are only allowed on enums without payload variants",
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_variant_payloads_are_checked() {
+        let errors = check(
+            "enum Msg { Move { x: int, y: int }, Write(string), Quit } \
+             fn foo() { let a := Msg::Write(1, 2); let b := Msg::Quit(); let c := Msg::Write; let d := Msg::Move { x: 1 }; let e := Msg::Jump; let f := Color::Red; }",
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "variant 'Msg::Write' expects 1 arguments, found 2",
+                "mismatched types: expected string, found int",
+                "variant 'Msg::Quit' can't be called",
+                "variant 'Msg::Write' has a payload, use 'Msg::Write(...)'",
+                "missing fields 'y' in 'Msg::Move' literal",
+                "enum 'Msg' has no variant 'Jump'",
+                "unknown enum 'Color'",
+            ]
+        );
+    }
+
+    #[test]
+    fn impl_methods_constructors_and_associated_fns() {
+        let errors = check(
+            "struct Point { x: int, y: int } \
+             impl Point { init(let x: int, let y: int) { print x + y; } fn origin() -> Point { return Point(0, 0); } fn sum(self) -> int { return self.x + self.y; } } \
+             impl Point { fn add(self, let other: Point) -> Point { return Point { x: self.x + other.x, y: self.y + other.y }; } } \
+             fn main() { let p := Point(1, 2); let q: Point = Point::origin().add(p); let s: int = q.sum(); }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn impl_declarations_are_checked() {
+        let errors = check("enum E { A } impl E { init() {} fn A() {} fn f(let x: int, self) {} } impl Nope {} fn g(self) {} struct S { x: int } impl S { fn h() {} fn h() {} }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "'self' parameter is only allowed in methods",
+                "enum 'E' can't have an 'init' constructor",
+                "'A' is already a variant of enum 'E'",
+                "'self' must be the first parameter",
+                "unknown type 'Nope'",
+                "'h' is already declared for type 'S'",
+            ]
+        );
+    }
+
+    #[test]
+    fn method_calls_are_checked() {
+        let errors = check(
+            "struct P { x: int } impl P { fn origin() -> P { return P { x: 0 }; } fn get(self) -> int { return self.x; } } \
+             fn main() { let z := P(1); let p := P { x: 1 }; let a := P::get(); let b := p.origin(); let c := p.get(1); let d := p.get; let e := p.x(); let f := P::nope(); print self; }",
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "struct 'P' has no 'init' constructor, use a 'P { ... }' literal",
+                "method 'P::get' takes 'self' and must be called on a value",
+                "'P::origin' doesn't take 'self', call it as 'P::origin(...)'",
+                "method 'P::get' expects 0 arguments, found 1",
+                "method 'P::get' must be called",
+                "value of type int is not callable",
+                "type 'P' has no function 'nope'",
+                "undeclared identifier 'self'",
+            ]
+        );
+    }
+
+    #[test]
+    fn defer_statements() {
+        let errors = check("fn foo(let n: int) -> int { defer print 1; for i in 0..n { defer { print i; for j in 0..i { if j > 2 { break; } } } if i > n { continue; } } return n; }");
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn defer_cant_leave_deferred_code() {
+        let errors = check("fn foo(let n: int) { while n > 0 { defer { if n > 0 { break; } continue; } } defer return; defer let x := 1; }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "'break' can't leave deferred code",
+                "'continue' can't leave deferred code",
+                "'return' isn't allowed in deferred code",
+                "variable declarations can't be deferred",
+            ]
+        );
+    }
+
+    #[test]
+    fn imported_items_are_qualified_with_the_module() {
+        let errors = check_modules(&[
+            ("", "let origin: geo::Point = geo::Point { x: geo::ZERO, y: 0 }; fn main() -> int { let p := geo::make(1); let c: geo::Color = geo::Color::Red; return p.sum() + geo::Point::unit().x; }"),
+            ("geo", "let ZERO := 0; struct Point { x: int, y: int } enum Color { Red } impl Point { fn unit() -> Point { return make(1); } fn sum(self) -> int { return self.x + self.y; } } fn make(let x: int) -> Point { return Point { x: x, y: ZERO }; }"),
+        ]);
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn modules_have_separate_namespaces() {
+        let errors = check_modules(&[
+            (
+                "",
+                "struct Point { x: int } fn make() {} fn main() { let p: Point = geo::make(); let q: int = make; print ZERO; print geo::NOPE; let c := geo::Color::Blue; }",
+            ),
+            ("geo", "let ZERO := 0; struct Point { x: int } enum Color { Red } fn make() -> Point { return Point { x: 1, y: 2 }; }"),
+        ]);
+
+        assert_eq!(
+            errors,
+            vec![
+                "geo.mv: struct 'Point' has no field 'y'",
+                "mismatched types: expected Point, found geo::Point",
+                "mismatched types: expected int, found fn()",
+                "undeclared identifier 'ZERO'",
+                "undeclared identifier 'geo::NOPE'",
+                "enum 'geo::Color' has no variant 'Blue'",
+            ]
+        );
+    }
+
+    #[test]
+    fn internal_items_are_only_visible_in_their_module() {
+        let errors = check_modules(&[
+            (
+                "",
+                "fn main() { print geo::SECRET; print geo::helper(); let h := geo::Hidden { x: 1 }; let p := geo::Point { x: 1, tag: 2 }; print p.tag; let m := geo::Mode::A; p.reset(); print p.get(); }",
+            ),
+            (
+                "geo",
+                "internal let SECRET := 1; internal fn helper() -> int { return SECRET; } internal struct Hidden { x: int } struct Point { x: int, internal tag: int = 0 } \
+                 internal enum Mode { A } impl Point { internal fn reset(self) {} fn get(self) -> int { self.reset(); return self.tag + helper(); } }",
+            ),
+        ]);
+
+        assert_eq!(
+            errors,
+            vec![
+                "global 'geo::SECRET' is internal to module 'geo' (note: 'geo::SECRET' is declared internal here at geo.mv:1:10)",
+                "function 'geo::helper' is internal to module 'geo' (note: 'geo::helper' is declared internal here at geo.mv:1:36)",
+                "struct 'geo::Hidden' is internal to module 'geo' (note: 'geo::Hidden' is declared internal here at geo.mv:1:83)",
+                "field 'tag' is internal to module 'geo' (note: 'tag' is declared internal here at geo.mv:1:140)",
+                "field 'tag' is internal to module 'geo' (note: 'tag' is declared internal here at geo.mv:1:140)",
+                "enum 'geo::Mode' is internal to module 'geo' (note: 'geo::Mode' is declared internal here at geo.mv:1:164)",
+                "method 'reset' is internal to module 'geo' (note: 'reset' is declared internal here at geo.mv:1:202)",
+            ]
+        );
+    }
+
+    #[test]
+    fn closures_and_function_values() {
+        let errors = check(
+            "fn apply(let f: fn(int) -> int, let x: int) -> int { return f(x); } fn double(let x: int) -> int { return x * 2; } \
+             fn main() { var k := 3; let add_k := |let x| x + k; let sum: fn(int, int) -> int = |let a, var acc| a + acc; \
+             let abs := |let x: int| -> int { if x > 0 { return x; } return -x; }; let log := || { print k; }; log(); \
+             print apply(add_k, 1) + apply(double, 2) + sum(1, 2) + abs(-3) + apply(|let x| x, 4); }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn var_captures_can_escape() {
+        let errors = check(
+            "var handler: fn() = || {}; fn counter() -> fn() -> int { var count := 0; return || -> int { count += 1; return count; }; } \
+             fn main() { var clicks := 0; handler = || { clicks += 1; }; let next := counter(); print next() + next(); }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn closures_are_checked() {
+        let errors = check(
+            "fn apply(let f: fn(int) -> int) -> int { return f(1, 2); } \
+             fn main() { let n := 1; print apply(|let x: float| x); print n(); loop { let f := || { break; }; let g := |let x| -> int { if x > 0 { return x; } }; let h := || { return 1; }; } }",
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "value of type fn(int) -> int expects 1 arguments, found 2",
+                "mismatched types: expected fn(int) -> int, found fn(float) -> float",
+                "value of type int is not callable",
+                "'break' outside of a loop",
+                "missing return in closure returning int",
+                "closure returns nothing, but a value of type int is returned",
+            ]
+        );
+    }
+
+    #[test]
+    fn generic_functions_and_structs() {
+        let errors = check(
+            "fn id<T>(let x: T) -> T { return x; } fn pick<T>(let c: bool, let a: T, let b: T) -> T { if c { return a; } return b; } \
+             fn apply<T, U>(let f: fn(T) -> U, let x: T) -> U { return f(x); } struct Pair<A, B> { first: A, second: B } \
+             impl Pair<A, B> { fn swap(self) -> Pair<B, A> { return Pair { first: self.second, second: self.first }; } \
+             fn map_first<C>(self, let f: fn(A) -> C) -> Pair<C, B> { return Pair { first: f(self.first), second: self.second }; } } \
+             fn main() { let a: int = id(1); let s: string = id::<string>(\"x\"); let f: float = pick(true, 1.5, 2.5); \
+             let p := Pair { first: 1, second: \"one\" }; let q: Pair<string, int> = p.swap(); let r: Pair<bool, string> = p.map_first(|let x: int| x > 0); \
+             let n: int = q.second; let g: fn(int) -> int = id::<int>; let l: string = apply(|let x: int| \"s\", 3); }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn generics_are_checked() {
+        let errors = check(
+            "fn id<T>(let x: T) -> T { return x; } fn add<T>(let a: T, let b: T) -> T { return a + b; } fn bad<T, T>() {} \
+             fn none<T>() -> int { return 0; } fn pick<T>(let a: T, let b: T) -> T { return a; } \
+             struct Pair<A, B> { first: A, second: B } struct Point { x: int } impl Point<T> {} \
+             fn main() { let a: string = id(1); let b := id::<int, int>(1); let c := none(); let d := id; \
+             let e: Pair<int> = Pair { first: 1, second: 2 }; let f: Point<int> = Point { x: 1 }; \
+             let g: Pair<int, int> = Pair { first: 1, second: \"x\" }; let h := pick(1, 2.5); }",
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "type parameter 'T' is already declared",
+                "struct 'Point' is not generic",
+                "invalid operands for binary operator: T and T",
+                "mismatched types: expected string, found int",
+                "function 'id' expects 1 type arguments, found 2",
+                "cannot infer type argument 'T' of function 'none'",
+                "generic function 'id' can't be used as a value without type arguments",
+                "struct 'Pair' expects 2 type arguments, found 1",
+                "struct 'Point' is not generic",
+                "mismatched types: expected Pair<int, int>, found Pair<int, string>",
+                "mismatched types: expected int, found float",
+            ]
+        );
+    }
+
+    #[test]
+    fn known_attributes() {
+        let errors = check(
+            "#[test] fn runs() {} #[inline] #[extern(\"c\")] fn add(let a: int, let b: int) -> int { return a + b; } \
+             #[deprecated(\"use Point\")] struct Pt { #[deprecated(\"use y\")] z: int } impl Pt { #[inline] fn get(self) -> int { return self.z; } }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn attributes_are_validated() {
+        let errors = check(
+            "#[test] fn with_args(let x: int) {} #[nope] fn a() {} #[inline(1)] #[inline] fn b() {} #[extern(\"rust\")] fn c() {} \
+             #[deprecated] #[test] struct S { #[extern(\"c\")] x: int } impl S { #[test] fn m(self) {} } fn d() { #[inline] print 1; }",
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "attribute 'deprecated' takes a single string literal argument",
+                "attribute 'test' can't be applied to structs",
+                "attribute 'extern' can't be applied to fields",
+                "test function 'with_args' can't take parameters or return a value",
+                "unknown attribute 'nope'",
+                "attribute 'inline' takes no arguments",
+                "attribute 'inline' is applied more than once",
+                "unsupported value \"rust\" for attribute 'extern', expected \"c\"",
+                "attribute 'test' can't be applied to methods",
+                "attribute 'inline' can't be applied to statements",
+            ]
+        );
+    }
+
+    #[test]
+    fn pointers() {
+        let errors = check(
+            "struct Point { x: int, y: int } impl Point { fn sum(self) -> int { return self.x + self.y; } } var zero := Point { x: 0, y: 0 }; fn origin() -> ^Point { return @zero; } \
+             fn main() { var p := Point { x: 1, y: 2 }; let pp: ^Point = @p; let x: int = pp^.x + pp.y + pp.sum(); let px: ^int = @p.x; \
+             let ppp := @pp; let y: int = ppp^^.y; var q: ?^Point = nil; if q == nil { print px^; } let z: int = origin()^.x; let w: ^int = @origin()^.y; }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn address_of_temporary() {
+        let errors = check(
+            "fn one() -> int { return 1; } struct Point { x: int } fn make() -> Point { return Point { x: 1 }; } \
+             fn main() { let a := @1; let b := @one(); let c := @make().x; let d := @one; }",
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "cannot take the address of a temporary value",
+                "cannot take the address of a temporary value",
+                "cannot take the address of a temporary value",
+                "cannot take the address of a temporary value",
+            ]
+        );
+    }
+
+    #[test]
+    fn dereference_non_pointer() {
+        let errors = check("fn main() { let n := 5; let d := n^; let h := nil^; }");
+
+        assert_eq!(errors, vec!["cannot dereference value of type int", "cannot dereference value of type nil"]);
+    }
+
+    #[test]
+    fn untyped_nil() {
+        let errors = check("fn main() { let e := nil; let p: ?int = nil; }");
+
+        assert_eq!(errors, vec!["cannot infer the type of 'e' from 'nil', add a type annotation"]);
+    }
+
+    #[test]
+    fn pointer_type_mismatch() {
+        let errors = check("fn main() { let n := 5; let f: ^int = @n; let g: ^float = f; }");
+
+        assert_eq!(errors, vec!["mismatched types: expected ^float, found ^int"]);
+    }
+
+    #[test]
+    fn arrays_and_slices() {
+        let errors = check(
+            "fn sum(let xs: [int]) -> int { var total := 0; for x in xs { print x; } return xs[0]; } \
+             fn main() { let a := [1, 2, 3]; let zeros: [float; 16] = [0.0; 16]; let n := 2; let first: int = a[0] + a[n]; \
+             let s: [int] = a[1..n]; let t: [int] = a[0..=2]; let u: [int] = s[0..1]; print sum(a[0..3]); print zeros[15]; \
+             let grid := [[0; 3]; 2]; let g: int = grid[1][2]; let p := @a; let q: int = p[1]; let r: ^int = @a[0]; }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn array_literal_element_types() {
+        let errors = check("fn main() { let a := [1, 2.5]; let b := []; }");
+
+        assert_eq!(errors, vec!["mismatched types: expected int, found float", "cannot infer the element type of an empty array"]);
+    }
+
+    #[test]
+    fn negative_array_length() {
+        let errors = check("fn main() { let c := [0; -1]; }");
+
+        assert_eq!(errors, vec!["array length must be a non-negative integer constant"]);
+    }
+
+    #[test]
+    fn constant_index_out_of_range() {
+        let errors = check("fn main() { let d := [1, 2, 3]; let s: [int] = d[0..2]; print d[3]; print d[-1]; print s[-1]; }");
+
+        assert_eq!(
+            errors,
+            vec!["index 3 is out of range for [int; 3]", "index -1 is out of range for [int; 3]", "index -1 is out of range for [int]"]
+        );
+    }
+
+    #[test]
+    fn array_index_must_be_int() {
+        let errors = check("fn main() { let d := [1, 2, 3]; print d[1.5]; }");
+
+        assert_eq!(errors, vec!["array index must be int, found float"]);
+    }
+
+    #[test]
+    fn constant_slice_bounds() {
+        let errors = check("fn main() { let d := [1, 2, 3]; print d[0..4]; print d[0..=3]; print d[2..1]; }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "slice end 4 is out of range for [int; 3]",
+                "slice end 3 is out of range for [int; 3]",
+                "slice start 2 is past its end 1"
+            ]
+        );
+    }
+
+    #[test]
+    fn index_non_array() {
+        let errors = check("fn main() { let n := 1; print n[0]; }");
+
+        assert_eq!(errors, vec!["cannot index value of type int"]);
+    }
+
+    #[test]
+    fn array_isnt_a_slice() {
+        let errors = check("fn sum(let xs: [int]) {} fn main() { let d := [1, 2, 3]; sum(d); }");
+
+        assert_eq!(errors, vec!["mismatched types: expected [int], found [int; 3]"]);
+    }
+
+    #[test]
+    fn increments_and_decrements() {
+        let errors = check(
+            "struct Point { x: int, y: float } impl Point { fn shift(var self) { self.x++; } } var count := 0; \
+             fn bump(var n: int, let p: ^Point) -> int { p.x++; --p^.y; return n++; } \
+             fn main() { var i := 0; let a: int = ++i; let b: int = i--; count++; var pt := Point { x: 1, y: 2.0 }; pt.x--; \
+             let f: float = ++pt.y; var xs := [1, 2, 3]; xs[i]++; let p := @pt; ++p.x; for var j in 0..3 { j++; } }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn increments_need_writable_numbers() {
+        let errors = check_modules(&[
+            (
+                "",
+                "import geo; fn one() -> int { return 1; } fn bump(let n: int) { n++; } \
+                 fn main() { let i := 0; i++; ++5; one()--; var s := \"a\"; s++; let xs := [1, 2]; --xs[0]; geo::origin++; one++; bump(1)++; }",
+            ),
+            ("geo", "let origin := 0;"),
+        ]);
+
+        assert_eq!(
+            errors,
+            vec![
+                "cannot increment 'n', it isn't declared with 'var' (note: 'n' is declared here at :1:55)",
+                "cannot increment 'i', it isn't declared with 'var' (note: 'i' is declared here at :1:84)",
+                "cannot increment a literal",
+                "cannot decrement the result of a call",
+                "cannot increment value of type string",
+                "cannot decrement 'xs', it isn't declared with 'var' (note: 'xs' is declared here at :1:134)",
+                "cannot increment 'geo::origin', it isn't declared with 'var' (note: 'geo::origin' is declared here at geo.mv:1:1)",
+                "cannot increment a function",
+                "cannot increment the result of a call",
+            ]
+        );
+    }
+
+    #[test]
+    fn assignments() {
+        let errors = check(
+            "struct Point { x: int, y: float } impl Point { fn reset(var self) { self.x = 0; self.y = 0.0; } } var total := 0; \
+             fn move(var p: Point, let q: ^Point) { p.x += 1; q.y -= 1.5; q^.x = p.x; } \
+             fn main() { var a := 1; var b := 2; a = b = 3; total += a * b; var s := \"a\"; s += \"b\"; var xs := [1, 2]; \
+             xs[0] *= 2; xs[1] /= xs[0]; let c: int = a = 4; var p: ?^int = nil; p = @a; let r := @b; r^ = 5; }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn receiver_needs_var_to_be_modified() {
+        let errors = check("struct P { x: int } impl P { fn reset(self) { self.x = 0; self = P { x: 1 }; } }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "cannot assign to 'self', it isn't declared with 'var' (note: 'self' is declared here at :1:39)",
+                "cannot assign to 'self', it isn't declared with 'var' (note: 'self' is declared here at :1:39)",
+            ]
+        );
+    }
+
+    #[test]
+    fn var_receiver_and_init_can_be_modified() {
+        let errors = check("struct P { x: int } impl P { init() { self.x = 0; } fn reset(var self) { self.x = 0; self = P { x: 1 }; } }");
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn assignments_need_writable_places() {
+        let errors = check(
+            "struct Point { x: int } fn make() -> Point { return Point { x: 1 }; } fn set(let n: int, var m: int) { n = 1; m = 2; } \
+             fn main() { let a := 1; a = 2; let pt := Point { x: 1 }; pt.x = 2; make().x = 3; 1 = 2; var b := true; \
+             b += true; var c := 1; c = 1.5; c -= 0.5; }",
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "cannot assign to 'n', it isn't declared with 'var' (note: 'n' is declared here at :1:82)",
+                "cannot assign to 'a', it isn't declared with 'var' (note: 'a' is declared here at :1:132)",
+                "cannot assign to 'pt', it isn't declared with 'var' (note: 'pt' is declared here at :1:151)",
+                "cannot assign to the result of a call",
+                "cannot assign to a literal",
+                "invalid operands for binary operator: bool and bool",
+                "mismatched types: expected int, found float",
+                "invalid operands for binary operator: int and float",
+            ]
+        );
+    }
+
+    #[test]
+    fn optionals() {
+        let errors = check(
+            "struct Node { value: int, next: ?^Node } impl Node { fn double(self) -> int { return self.value * 2; } } \
+             fn find(let n: int) -> ?Node { if n > 0 { return Node { value: n, next: nil }; } return nil; } \
+             fn main() { let a: ?int = 1; let b: ?int = nil; let c: int = a ?? 0; let d: ?int = a ?? b; var n := find(1); \
+             let v: ?int = n?.value; let w: ?int = n?.next?.value; let x: int = n?.double() ?? 0; if n == nil { n = find(2); } \
+             if let node = n { let y: int = node.value; if let next = node.next { let z: int = next.value; } } else { print 0; } }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn optionals_need_unwrapping() {
+        let errors = check(
+            "struct Node { value: int } fn find() -> ?Node { return nil; } fn take(let n: int) {} \
+             fn main() { let a: ?int = 1; let b: int = a; let c := a + 1; take(a); let d: int = nil; let e: ^int = nil; \
+             let n := find(); let f := n.value; let g := 1 ?? 2; let h: int = a ?? \"x\"; if let i = 5 {} let j := b?.value; \
+             if let k = a { k = 2; } }",
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "mismatched types: expected int, found ?int",
+                "invalid operands for binary operator: ?int and int",
+                "mismatched types: expected int, found ?int",
+                "mismatched types: expected int, found nil",
+                "mismatched types: expected ^int, found nil",
+                "value of type ?Node may be nil, unwrap it with 'if let' or use '?.'",
+                "left operand of '??' must be optional, found int",
+                "mismatched types: expected int, found string",
+                "'if let' needs an optional value, found int",
+                "'?.' needs an optional value, found int",
+                "cannot assign to 'k', it isn't declared with 'var' (note: 'k' is declared here at :1:303)",
+            ]
+        );
+    }
+
+    #[test]
+    fn consts() {
+        let errors = check_modules(&[
+            (
+                "",
+                "import geo; const CELLS: int = WIDTH * HEIGHT; const WIDTH: int = geo::SIZE * 2; const HEIGHT: int = fact(3); \
+                 const fn fact(let n: int) -> int { if n <= 1 { return 1; } let rest := fact(n - 1); return n * rest; } \
+                 const BIG: bool = CELLS > 100 and !false; const GREETING: string = \"hello\" + \" \" + geo::NAME; \
+                 struct Grid { cells: [int; CELLS] } fn main() { let g := Grid { cells: [0; 48] }; let row: [float; WIDTH] = [0.0; 8]; \
+                 print g.cells[CELLS - 1]; switch CELLS { WIDTH * HEIGHT -> print BIG; HEIGHT, 1 -> print GREETING; } }",
+            ),
+            ("geo", "const SIZE: int = 4; const NAME: string = \"geo\";"),
+        ]);
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn const_cycles() {
+        let errors = check("const C: int = D + 1; const D: int = E; const E: int = C * 2;");
+
+        assert_eq!(errors, vec!["const 'C' depends on itself: C -> D -> E -> C"]);
+    }
+
+    #[test]
+    fn non_const_calls() {
+        let errors = check("fn next() -> int { return 1; } const B: int = next();");
+
+        assert_eq!(errors, vec!["'next' is not a const function"]);
+    }
+
+    #[test]
+    fn non_const_values() {
+        let errors = check("var count := 1; const A: int = count + 1; fn main() { let n := 3; let xs: [int; n] = [0; 3]; }");
+
+        assert_eq!(errors, vec!["'count' is not a constant", "array length must be a non-negative integer constant"]);
+    }
+
+    #[test]
+    fn const_overflow() {
+        let errors = check("const G: int = 2147483647 + 1;");
+
+        assert_eq!(errors, vec!["overflow in constant expression"]);
+    }
+
+    #[test]
+    fn const_division_by_zero() {
+        let errors = check("const F: int = 1 / (2 - 2);");
+
+        assert_eq!(errors, vec!["division by zero in constant expression"]);
+    }
+
+    #[test]
+    fn const_types() {
+        let errors = check("const H: float = 1; const I: [int; 2] = [1, 2];");
+
+        assert_eq!(
+            errors,
+            vec!["mismatched types: expected float, found int", "const 'I' must be an int, float, bool or string, found [int; 2]"]
+        );
+    }
+
+    #[test]
+    fn const_fn_body_restrictions() {
+        let errors = check("const fn loops(let n: int) -> int { while true {} return n; }");
+
+        assert_eq!(errors, vec!["const function 'loops' can only contain 'let', 'if' and 'return' statements"]);
+    }
+
+    #[test]
+    fn const_fn_recursion_depth() {
+        let errors = check("const fn forever(let n: int) -> int { return forever(n + 1); } const J: int = forever(0);");
+
+        assert_eq!(errors, vec!["constant evaluation exceeded 64 nested calls"]);
+    }
+
+    #[test]
+    fn const_evaluation_is_bounded() {
+        let errors = check("const fn f(let n: int) -> int { if n == 0 { return 1; } return f(n - 1) + f(n - 1) - f(n - 1); } const X: int = f(40);");
+
+        assert_eq!(errors, vec!["constant evaluation exceeded 100000 steps"]);
+    }
+
+    #[test]
+    fn patterns() {
+        let errors = check(
+            "enum Shape { Circle(float), Rect { w: float, h: float }, Empty } struct Point { x: int, y: int } const LIMIT: int = 10; \
+             fn area(let s: Shape) -> float { switch s { Shape::Circle(r) if r < 0.0 -> return 0.0; Shape::Circle(r) -> return r * r; \
+             Shape::Rect { w, h } -> return w * h; Shape::Empty -> return 0.0; } } \
+             fn quadrant(let p: Point) -> int { switch p { Point { x: 0, y: 0 } -> return 0; Point { x: 0, .. } | Point { y: 0, .. } -> return 1; \
+             Point { x: LIMIT, y } if y > 0 -> return y; Point { x, y } -> return x + y; } } \
+             fn classify(let n: int) -> string { switch n { 0 -> return \"zero\"; 1..LIMIT -> return \"small\"; LIMIT -> return \"limit\"; \
+             m if m < 0 -> return \"negative\"; _ -> return \"big\"; } } \
+             fn main() { let s := Shape::Rect { w: 1.0, h: 2.0 }; switch s { Shape::Empty -> fall; Shape::Rect { .. } -> print 1; else -> print 2; } }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn exhaustive_switch_in_other_module_doesnt_count() {
+        // Both switches are at 1:30 of their file.
+        let errors = check_modules(&[
+            ("", "fn miss(let n: int) -> int { switch n { 1 -> return 1; } }"),
+            ("other", "fn pick(let n: int) -> int { switch n { 1 -> return 1; else -> return 2; } }"),
+        ]);
+
+        assert_eq!(errors, vec!["missing return in function 'miss' returning int"]);
+    }
+
+    #[test]
+    fn switch_must_cover_every_variant() {
+        let errors = check("enum Shape { Circle(float), Rect { w: float, h: float }, Empty } fn f(let s: Shape) { switch s { Shape::Circle(r) -> print r; } }");
+
+        assert_eq!(errors, vec!["switch on Shape doesn't cover 'Shape::Rect', 'Shape::Empty', add arms for them or an 'else' arm"]);
+    }
+
+    #[test]
+    fn arms_after_wildcard_are_unreachable() {
+        let errors = check("enum Shape { Circle(float), Empty } fn f(let s: Shape) { switch s { _ -> print 1; Shape::Empty -> print 2; } }");
+
+        assert_eq!(errors, vec!["unreachable switch arm"]);
+    }
+
+    #[test]
+    fn repeated_variant_pattern_is_unreachable() {
+        let errors = check("enum Shape { Circle(float), Empty } fn f(let s: Shape) { switch s { Shape::Empty, Shape::Empty -> print 1; else -> print 2; } }");
+
+        assert_eq!(errors, vec!["unreachable pattern, its variant is already covered"]);
+    }
+
+    #[test]
+    fn tuple_variant_patterns_match_payload() {
+        let errors = check("enum Shape { Circle(float), Empty } fn f(let s: Shape) { switch s { Shape::Circle(a, b) -> print 1; Shape::Empty(x) -> print 2; else -> print 3; } }");
+
+        assert_eq!(errors, vec!["variant 'Shape::Circle' expects 1 patterns, found 2", "variant 'Shape::Empty' has no payload"]);
+    }
+
+    #[test]
+    fn variant_patterns_use_variant_form() {
+        let errors = check("enum Shape { Circle(float), Rect { w: float, h: float } } fn f(let s: Shape) { switch s { Shape::Circle -> print 1; Shape::Rect(w) -> print 2; else -> print 3; } }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "variant 'Shape::Circle' has a payload, match it with 'Shape::Circle(...)'",
+                "variant 'Shape::Rect' has named fields, match it with 'Shape::Rect { ... }'"
+            ]
+        );
+    }
+
+    #[test]
+    fn struct_variant_patterns_name_every_field() {
+        let errors = check("enum Shape { Rect { w: float, h: float }, Empty } fn f(let s: Shape) { switch s { Shape::Rect { w } -> print 1; Shape::Rect { z, .. } -> print 2; else -> print 3; } }");
+
+        assert_eq!(
+            errors,
+            vec!["missing fields 'h' in 'Shape::Rect' pattern, use '..' to ignore them", "variant 'Shape::Rect' has no field 'z'"]
+        );
+    }
+
+    #[test]
+    fn alternatives_bind_same_variables() {
+        let errors = check("enum Shape { Circle(float), Square(float), Empty } fn f(let s: Shape) { switch s { Shape::Circle(r) | Shape::Empty -> print 1; else -> print 2; } }");
+
+        assert_eq!(errors, vec!["all alternatives of a switch arm must bind the same variables"]);
+    }
+
+    #[test]
+    fn variant_pattern_needs_enum_value() {
+        let errors = check("enum Shape { Circle(float), Empty } fn f() { switch 1 { Shape::Empty -> print 1; else -> print 2; } }");
+
+        assert_eq!(errors, vec!["mismatched types: expected int, found Shape"]);
+    }
+
+    #[test]
+    fn fall_into_binding_arm() {
+        let errors = check("enum Shape { Circle(float), Empty } fn f(let s: Shape) { switch s { Shape::Empty -> fall; Shape::Circle(r) -> print r; } }");
+
+        assert_eq!(errors, vec!["'fall' can't continue into an arm that binds variables"]);
+    }
+
+    #[test]
+    fn tuples() {
+        let errors = check(
+            "struct Pair<T> { value: T } fn divmod(let a: int, let b: int) -> (int, int) { return (a / b, a - b * (a / b)); } \
+             fn first<T>(let pair: (T, string)) -> T { return pair.0; } var origin: (float, float) = (0.0, 0.0); \
+             fn main() { let (q, r) := divmod(7, 2); var (x, _): (int, ?string) = (q + r, nil); x += 1; \
+             var t := (1, (\"a\", true)); t.0 = 2; t.1.0 = \"b\"; let flag: bool = t.1.1; let n: int = first((3, \"c\")); \
+             let p := Pair { value: (q, 1.5) }; let f: float = p.value.1 + origin.0; let g: fn(int, int) -> (int, int) = divmod; }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn tuple_returns_are_checked() {
+        let errors = check("fn pair() -> (int, string) { return (1, 2); }");
+
+        assert_eq!(errors, vec!["mismatched return type: expected (int, string), found (int, int)"]);
+    }
+
+    #[test]
+    fn tuple_field_out_of_range() {
+        let errors = check("fn main() { let t := (1, \"a\"); print t.2; }");
+
+        assert_eq!(errors, vec!["type (int, string) has no field '2'"]);
+    }
+
+    #[test]
+    fn destructuring_needs_matching_tuple() {
+        let errors = check("fn main() { let t := (1, \"a\"); let (a, b, c) := t; let (d, e) := 5; }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "cannot destructure value of type (int, string) into 3 variables",
+                "cannot destructure value of type int into 2 variables"
+            ]
+        );
+    }
+
+    #[test]
+    fn tuple_fields_of_let_are_immutable() {
+        let errors = check("fn main() { let t := (1, \"a\"); t.0 = 3; }");
+
+        assert_eq!(errors, vec!["cannot assign to 't', it isn't declared with 'var' (note: 't' is declared here at :1:13)"]);
+    }
+
+    #[test]
+    fn tuple_types_must_match() {
+        let errors = check("fn main() { let t := (1, \"a\"); let s: (int, int) = t; }");
+
+        assert_eq!(errors, vec!["mismatched types: expected (int, int), found (int, string)"]);
+    }
+
+    #[test]
+    fn destructured_names_are_unique() {
+        let errors = check("fn main() { let (f, f) := (1, 2); }");
+
+        assert_eq!(errors, vec!["'f' is already declared in this scope"]);
+    }
+
+    #[test]
+    fn labeled_loops() {
+        let errors = check(
+            "fn find(let grid: [[int; 3]; 3], let target: int) -> int { var found := -1; \
+             rows: for row in grid { for cell in row { if cell == target { found = cell; break rows; } if cell < 0 { continue rows; } } } \
+             return found; } \
+             fn first_even(let xs: [int]) -> int { var i := 0; let even := loop { let x := xs[i]; if x / 2 * 2 == x { break x; } i++; }; return even; } \
+             fn spin() -> int { outer: loop { loop { break outer; } } return 0; } \
+             fn forever() -> int { outer: loop { inner: loop { break inner; } } }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn break_name_prefers_label_over_variable() {
+        let errors = check("fn main() { let rows := 1; rows: loop { let a := loop { break rows; }; } }");
+
+        assert_eq!(errors, vec!["'loop' expression needs a 'break' with a value"]);
+    }
+
+    #[test]
+    fn break_name_without_label_is_value() {
+        let errors = check("fn main() { let v := 1; while true { break v; } }");
+
+        assert_eq!(errors, vec!["'break' with a value can only leave a 'loop' expression"]);
+    }
+
+    #[test]
+    fn labels_cant_shadow_enclosing_labels() {
+        let errors = check("fn main() { outer: loop { outer: while true { break; } break outer; } }");
+
+        assert_eq!(errors, vec!["label 'outer' shadows the label of an enclosing loop (note: 'outer' is declared here at :1:20)"]);
+    }
+
+    #[test]
+    fn jumps_need_known_labels() {
+        let errors = check("fn main() { loop { continue inner; } }");
+
+        assert_eq!(errors, vec!["unknown label 'inner'"]);
+    }
+
+    #[test]
+    fn break_value_needs_loop_expression() {
+        let errors = check("fn main() { loop { break 1; } }");
+
+        assert_eq!(errors, vec!["'break' with a value can only leave a 'loop' expression"]);
+    }
+
+    #[test]
+    fn loop_expression_needs_break_value() {
+        let errors = check("fn main() { let a := loop { break; }; let c := loop {}; }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "'break' out of a 'loop' expression needs a value",
+                "'loop' expression needs a 'break' with a value",
+                "'loop' expression needs a 'break' with a value"
+            ]
+        );
+    }
+
+    #[test]
+    fn break_values_share_a_type() {
+        let errors = check("fn main() { let b := loop { break 1; break \"x\"; }; }");
+
+        assert_eq!(errors, vec!["mismatched types: expected int, found string"]);
+    }
+
+    #[test]
+    fn deferred_break_cant_leave_loop() {
+        let errors = check("fn main() { outer: loop { defer { break outer; } break; } }");
+
+        assert_eq!(errors, vec!["'break' can't leave deferred code"]);
+    }
+
+    #[test]
+    fn named_and_default_args() {
+        let errors = check(
+            "const PORT: int = 80; fn connect(let host: string, let port: int = PORT, let secure: bool = PORT == 443) -> int { return port; } \
+             struct Window { width: int, height: int } impl Window { init(let width: int = 640, let height: int = 480) { self.width = width; self.height = height; } \
+             fn resize(var self, let width: int = 800, let height: int = 600) { self.width = width; self.height = height; } } \
+             const fn area(let w: int, let h: int = 2) -> int { return w * h; } const AREA: int = area(h: 3, w: 4) + area(5); \
+             fn pick<T>(let a: T, let b: T, let first: bool = true) -> T { return first ? a : b; } \
+             fn main() { let a := connect(\"x\"); let b := connect(\"x\", 8080); let c := connect(host: \"x\", secure: true); \
+             let d := connect(\"x\", secure: false, port: 1); var w := Window(height: 720); w.resize(height: 900); \
+             let e := Window(); let f: string = pick(\"a\", b: \"b\", first: false); let g: [int; AREA] = [0; 22]; }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn defaults_must_be_trailing() {
+        let errors = check("fn bad(let a: int = 1, let timeout: int) {}");
+
+        assert_eq!(errors, vec!["parameter 'timeout' needs a default value, it follows a parameter with one"]);
+    }
+
+    #[test]
+    fn positional_args_come_first() {
+        let errors = check("fn connect(let host: string, let port: int = 80) {} fn main() { connect(port: 1, \"x\"); }");
+
+        assert_eq!(errors, vec!["positional argument can't follow a named argument"]);
+    }
+
+    #[test]
+    fn args_bind_once() {
+        let errors = check("fn connect(let host: string, let port: int = 80) {} fn main() { connect(\"x\", host: \"y\"); }");
+
+        assert_eq!(errors, vec!["argument for parameter 'host' is given more than once"]);
+    }
+
+    #[test]
+    fn named_args_need_known_parameters() {
+        let errors = check("fn connect(let host: string, let port: int = 80) {} fn main() { connect(\"x\", speed: 3); }");
+
+        assert_eq!(errors, vec!["function 'connect' has no parameter 'speed'"]);
+    }
+
+    #[test]
+    fn params_without_default_need_args() {
+        let errors = check("fn connect(let host: string, let port: int = 80) {} fn main() { connect(port: 1); }");
+
+        assert_eq!(errors, vec!["missing arguments 'host' in call to function 'connect'"]);
+    }
+
+    #[test]
+    fn arg_count_respects_defaults() {
+        let errors = check("fn connect(let host: string, let port: int = 80) {} fn main() { connect(\"a\", 1, 2); connect(); }");
+
+        assert_eq!(
+            errors,
+            vec!["function 'connect' expects at most 2 arguments, found 3", "function 'connect' expects at least 1 arguments, found 0"]
+        );
+    }
+
+    #[test]
+    fn named_args_are_type_checked() {
+        let errors = check("fn add(let a: int, let b: int) -> int { return a + b; } fn main() { add(a: 1, b: \"2\"); }");
+
+        assert_eq!(errors, vec!["mismatched types: expected int, found string"]);
+    }
+
+    #[test]
+    fn closure_params_have_no_defaults() {
+        let errors = check("fn main() { let f := |let x: int = 1| x; }");
+
+        assert_eq!(errors, vec!["closure parameters can't have default values"]);
+    }
+
+    #[test]
+    fn fn_values_take_no_named_args() {
+        let errors = check("fn add(let a: int, let b: int) -> int { return a + b; } fn main() { let g := add; g(a: 1, b: 2); }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "value of type fn(int, int) -> int can't take named arguments, found 'a'",
+                "value of type fn(int, int) -> int can't take named arguments, found 'b'"
+            ]
+        );
+    }
+
+    #[test]
+    fn variadics() {
+        let errors = check(
+            "fn log(let fmt: string, let args: ...any) -> int { var count := 0; for arg in args { print arg; count += 1; } return count; } \
+             fn sum(let nums: ...int) -> int { var total := 0; for n in nums { total += n; } return total; } \
+             fn first<T>(let fallback: T, let rest: ...T) -> T { for value in rest { return value; } return fallback; } \
+             struct P { x: int } fn main() { let a := log(\"none\"); let b := log(\"mixed\", 1, \"two\", 3.0, P { x: 4 }); \
+             let nums := [1, 2, 3]; let c: int = sum() + sum(1, 2) + sum(...nums[0..2]); let d: string = first(\"x\", \"y\", \"z\"); \
+             let e := first(fallback: 1); let f: fn([int]) -> int = sum; }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn variadic_param_is_last() {
+        let errors = check("fn bad(let args: ...int, let last: int) {}");
+
+        assert_eq!(errors, vec!["variadic parameter 'args' must be the last parameter"]);
+    }
+
+    #[test]
+    fn variadic_param_has_no_default() {
+        let errors = check("fn log(let args: ...int = 1) {}");
+
+        assert_eq!(errors, vec!["variadic parameter 'args' can't have a default value"]);
+    }
+
+    #[test]
+    fn const_fns_arent_variadic() {
+        let errors = check("const fn count(let xs: ...int) -> int { return 0; }");
+
+        assert_eq!(errors, vec!["const function 'count' can't have a variadic parameter"]);
+    }
+
+    #[test]
+    fn variadic_args_are_type_checked() {
+        let errors = check("fn log(let fmt: string, let args: ...int) {} fn main() { log(\"x\", 1, \"2\"); }");
+
+        assert_eq!(errors, vec!["mismatched types: expected int, found string"]);
+    }
+
+    #[test]
+    fn spread_is_the_only_variadic_arg() {
+        let errors = check("fn log(let fmt: string, let args: ...int) {} fn main() { let nums := [1, 2]; log(\"x\", 1, ...nums[0..2]); log(...nums[0..2]); }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "spread argument must be the only argument for variadic parameter 'args'",
+                "spread argument must be the only argument for variadic parameter 'args'"
+            ]
+        );
+    }
+
+    #[test]
+    fn spread_needs_a_slice() {
+        let errors = check("fn log(let args: ...int) {} fn main() { let nums := [1, 2]; log(...nums); }");
+
+        assert_eq!(errors, vec!["mismatched types: expected [int], found [int; 2]"]);
+    }
+
+    #[test]
+    fn variadic_param_isnt_named() {
+        let errors = check("fn log(let fmt: string, let args: ...int) {} fn main() { log(\"x\", args: 1); }");
+
+        assert_eq!(errors, vec!["variadic parameter 'args' can't be given by name"]);
+    }
+
+    #[test]
+    fn variadic_fn_needs_leading_args() {
+        let errors = check("fn log(let fmt: string, let args: ...int) {} fn main() { log(); }");
+
+        assert_eq!(errors, vec!["function 'log' expects at least 1 arguments, found 0"]);
+    }
+
+    #[test]
+    fn spread_needs_variadic_param() {
+        let errors = check("fn add(let a: int, let b: int) -> int { return a + b; } fn main() { let nums := [1, 2]; add(1, ...nums[0..2]); }");
+
+        assert_eq!(errors, vec!["function 'add' has no variadic parameter to spread into"]);
+    }
+
+    #[test]
+    fn closure_params_arent_variadic() {
+        let errors = check("fn main() { let f := |let xs: ...int| 0; }");
+
+        assert_eq!(errors, vec!["closure parameters can't be variadic"]);
+    }
+
+    #[test]
+    fn fn_values_dont_take_spread() {
+        let errors = check("fn add(let a: int, let b: int) -> int { return a + b; } fn main() { let nums := [1, 2]; let g := add; g(...nums[0..2]); }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "value of type fn(int, int) -> int has no variadic parameter to spread into",
+                "value of type fn(int, int) -> int expects 2 arguments, found 1"
+            ]
+        );
+    }
+
+    #[test]
+    fn interfaces() {
+        let errors = check(
+            "interface Shape { fn area(self) -> int; fn scaled(self, let factor: int) -> Self; } interface Named { fn name(self) -> string; } \
+             struct Square { side: int } enum Unit { One } struct Boxed<T> { value: T } \
+             impl Shape for Square { fn area(self) -> int { return self.side * self.side; } fn scaled(self, let factor: int) -> Square { return Square { side: self.side * factor }; } } \
+             impl Named for Square { fn name(self) -> string { return \"square\"; } } impl Named for Unit { fn name(self) -> string { return \"unit\"; } } \
+             impl Named for Boxed<T> { fn name(self) -> string { return \"boxed\"; } } \
+             fn total<T: Shape>(let shapes: ...T) -> int { var sum := 0; for shape in shapes { sum += shape.scaled(2).area(); } return sum; } \
+             fn describe<T: Shape + Named>(let value: T) -> string { let big: T = value.scaled(factor: 10); print big.area(); return value.name(); } \
+             fn label<T: Named>(let value: T) -> string { return value.name(); } fn relabel<T: Named>(let value: T) -> string { return label(value); } \
+             fn main() { let s := Square { side: 2 }; let a: int = total(s, s.scaled(3)); let d: string = describe(s); \
+             let n: string = label(Unit::One) + relabel(Boxed { value: 1 }) + s.name(); let f: fn(Square) -> string = label::<Square>; }",
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn interface_methods_match_by_type() {
+        let errors = check(
+            "interface Same { fn same(self, let other: Self) -> bool; } struct Boxed<T> { value: T } struct Pair { a: int } \
+             impl Same for Boxed<T> { fn same(self, let other: Boxed<T>) -> bool { return true; } } \
+             impl Same for Pair { fn same(self, let other: Boxed<int>) -> bool { return false; } }",
+        );
+
+        assert_eq!(
+            errors,
+            vec!["method 'same' doesn't match interface 'Same': expected fn(self, Pair) -> bool, found fn(self, Boxed<int>) -> bool (note: 'same' is declared here at :1:18)"]
+        );
+    }
+
+    #[test]
+    fn only_fn_type_params_have_bounds() {
+        let errors = check("interface Shape { fn area(self) -> int; } struct Wrap<T: Shape> { value: T }");
+
+        assert_eq!(errors, vec!["type parameter 'T' can't have bounds, only those of functions can"]);
+    }
+
+    #[test]
+    fn interface_methods_take_self() {
+        let errors = check("interface Bad { fn make() -> int; }");
+
+        assert_eq!(errors, vec!["interface method 'make' must take 'self'"]);
+    }
+
+    #[test]
+    fn interface_methods_arent_generic() {
+        let errors = check("interface Bad { fn pick<T>(self, let x: T) -> T; }");
+
+        assert_eq!(errors, vec!["interface method 'pick' can't be generic"]);
+    }
+
+    #[test]
+    fn interface_methods_have_no_defaults() {
+        let errors = check("interface Bad { fn opt(self, let x: int = 1); }");
+
+        assert_eq!(errors, vec!["interface method parameters can't have default values"]);
+    }
+
+    #[test]
+    fn interface_methods_are_unique() {
+        let errors = check("interface Bad { fn make(self); fn make(self); }");
+
+        assert_eq!(errors, vec!["'make' is already declared in interface 'Bad'"]);
+    }
+
+    #[test]
+    fn bounds_need_known_interfaces() {
+        let errors = check("fn largest<U: Missing>(let a: U) {}");
+
+        assert_eq!(errors, vec!["unknown interface 'Missing'"]);
+    }
+
+    #[test]
+    fn impl_methods_match_interface() {
+        let errors = check("interface Shape { fn area(self) -> int; } struct Square { side: int } impl Shape for Square { fn area(self) -> float { return 1.0; } }");
+
+        assert_eq!(
+            errors,
+            vec!["method 'area' doesn't match interface 'Shape': expected fn(self) -> int, found fn(self) -> float (note: 'area' is declared here at :1:19)"]
+        );
+    }
+
+    #[test]
+    fn impl_defines_only_interface_methods() {
+        let errors = check(
+            "interface Shape { fn area(self) -> int; } struct Square { side: int } impl Shape for Square { fn area(self) -> int { return 1; } fn perimeter(self) -> \
+             int { return 4; } }",
+        );
+
+        assert_eq!(errors, vec!["'perimeter' is not a method of interface 'Shape'"]);
+    }
+
+    #[test]
+    fn impl_defines_every_interface_method() {
+        let errors = check("interface Shape { fn area(self) -> int; fn grow(self, let by: int) -> Self; } struct Square { side: int } impl Shape for Square { fn area(self) -> int { return 1; } }");
+
+        assert_eq!(errors, vec!["missing methods 'grow' in impl of 'Shape' for 'Square'"]);
+    }
+
+    #[test]
+    fn interface_is_implemented_once() {
+        let errors = check("interface Shape { fn area(self) -> int; } struct Circle { r: int } impl Shape for Circle { fn area(self) -> int { return 3; } } impl Shape for Circle {}");
+
+        assert_eq!(
+            errors,
+            vec!["interface 'Shape' is already implemented for type 'Circle' (note: 'Shape' is first implemented here at :1:68)"]
+        );
+    }
+
+    #[test]
+    fn impl_methods_cant_clash() {
+        let errors = check(
+            "interface Shape { fn area(self) -> int; } interface Sized { fn area(self) -> int; } struct Circle { r: int } \
+             impl Shape for Circle { fn area(self) -> int { return 3; } } impl Sized for Circle { fn area(self) -> int { return 3; } }",
+        );
+
+        assert_eq!(errors, vec!["'area' is already declared for type 'Circle'"]);
+    }
+
+    #[test]
+    fn impl_needs_known_interface() {
+        let errors = check("struct Circle { r: int } impl Nope for Circle {}");
+
+        assert_eq!(errors, vec!["unknown interface 'Nope'"]);
+    }
+
+    #[test]
+    fn bound_methods_are_unambiguous() {
+        let errors = check("interface Shape { fn area(self) -> int; } interface Sized { fn area(self) -> int; } fn largest<T: Shape + Sized>(let a: T) -> int { return a.area(); }");
+
+        assert_eq!(errors, vec!["method 'area' of 'T' is ambiguous, interfaces 'Shape' and 'Sized' both declare it"]);
+    }
+
+    #[test]
+    fn bounds_expose_only_methods() {
+        let errors = check("interface Shape { fn area(self) -> int; } fn measure<T: Shape>(let a: T) -> int { return a.side; }");
+
+        assert_eq!(errors, vec!["type T has no field 'side'"]);
+    }
+
+    #[test]
+    fn type_args_implement_bounds() {
+        let errors = check("interface Shape { fn area(self) -> int; } fn measure<T: Shape>(let a: T) {} fn main() { measure(3); let f := measure::<bool>; }");
+
+        assert_eq!(
+            errors,
+            vec![
+                "type int doesn't implement interface 'Shape', required by 'T' of function 'measure'",
+                "type bool doesn't implement interface 'Shape', required by 'T' of function 'measure'",
+            ]
+        );
+    }
+
+    #[test]
+    fn interfaces_arent_types() {
+        let errors = check("interface Shape { fn area(self) -> int; } fn main() { let s: Shape = 1; }");
+
+        assert_eq!(errors, vec!["interface 'Shape' can't be used as a type, use a type parameter bounded by it"]);
+    }
+
+    #[test]
+    fn interfaces_across_modules() {
+        let errors = check_modules(&[
+            (
+                "",
+                "struct Point { x: int } impl shapes::Shape for Point { fn area(self) -> int { return 0; } } impl shapes::Secret for Point {} \
+                 fn main() { let p := Point { x: 1 }; let a: int = shapes::measure(p); }",
+            ),
+            (
+                "shapes",
+                "interface Shape { fn area(self) -> int; } internal interface Secret {} fn measure<T: Shape>(let value: T) -> int { return value.area(); }",
+            ),
+        ]);
+
+        assert_eq!(
+            errors,
+            vec!["interface 'shapes::Secret' is internal to module 'shapes' (note: 'shapes::Secret' is declared internal here at shapes.mv:1:52)"]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Ty {
+    Int,
+    Float,
+    Bool,
+    Str,
+    Void,
+    /// `any`, which every value converts to. Nothing can be done with it except passing it on.
+    Any,
+    /// Fixed-size array, which holds its elements.
+    Array(Box<Ty>, usize),
+    /// View into an array or a part of it. Arrays don't convert to slices implicitly, `a[0..n]` makes one.
+    Slice(Box<Ty>),
+    /// Pointer that always points to a value, `?^T` is the pointer that can be `nil`.
+    Pointer(Box<Ty>),
+    /// `?T`, either a value of `T` or `nil`. A `T` converts to it implicitly, the other way needs unwrapping.
+    Optional(Box<Ty>),
+    /// Type of `nil`, which converts to any optional.
+    Nil,
+    /// `(int, string)`, which holds its elements.
+    Tuple(Vec<Ty>),
+    /// Struct with its type arguments, which are empty unless the struct is generic.
+    Struct(String, Vec<Ty>),
+    Enum(String),
+    /// Type parameter of the generic item being checked, opaque to its body.
+    Param(String),
+    /// Parameter types and return type, which is void for functions returning nothing.
+    Fn(Vec<Ty>, Box<Ty>),
+    /// Type of an expression that already failed to check, compatible with everything to avoid cascading errors.
+    Error,
+}
+
+impl Ty {
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Ty::Int | Ty::Float)
+    }
+
+    pub fn is_assignable_from(&self, other: &Ty) -> bool {
+        match (self, other) {
+            (Ty::Any, _) => true,
+            (Ty::Optional(inner), Ty::Optional(other)) => inner.is_assignable_from(other),
+            (Ty::Optional(inner), other) => *other == Ty::Nil || inner.is_assignable_from(other),
+            (Ty::Tuple(elems), Ty::Tuple(others)) => elems.len() == others.len() && elems.iter().zip(others).all(|(elem, other)| elem.is_assignable_from(other)),
+            _ => self == other || *self == Ty::Error || *other == Ty::Error,
+        }
+    }
+
+    /// Replaces the bound type parameters, leaving the others as they are.
+    pub fn substitute(&self, bindings: &HashMap<String, Ty>) -> Ty {
+        match self {
+            Ty::Param(name) => bindings.get(name).cloned().unwrap_or_else(|| self.clone()),
+            Ty::Array(elem, len) => Ty::Array(Box::new(elem.substitute(bindings)), *len),
+            Ty::Slice(elem) => Ty::Slice(Box::new(elem.substitute(bindings))),
+            Ty::Pointer(pointee) => Ty::Pointer(Box::new(pointee.substitute(bindings))),
+            Ty::Optional(inner) => Ty::Optional(Box::new(inner.substitute(bindings))),
+            Ty::Tuple(elems) => Ty::Tuple(elems.iter().map(|elem| elem.substitute(bindings)).collect()),
+            Ty::Struct(name, args) => Ty::Struct(name.clone(), args.iter().map(|arg| arg.substitute(bindings)).collect()),
+            Ty::Fn(params, return_ty) => Ty::Fn(params.iter().map(|param| param.substitute(bindings)).collect(), Box::new(return_ty.substitute(bindings))),
+            _ => self.clone(),
+        }
+    }
+
+    /// Binds the type parameters found in `self` by matching it against the type of a value. The first binding of a
+    /// parameter wins, a conflicting one shows up as a mismatch once the parameters are substituted.
+    pub fn infer(&self, actual: &Ty, bindings: &mut HashMap<String, Ty>) {
+        match (self, actual) {
+            (Ty::Param(name), _) => {
+                bindings.entry(name.clone()).or_insert_with(|| actual.clone());
+            }
+            (Ty::Array(elem, _), Ty::Array(actual, _)) | (Ty::Slice(elem), Ty::Slice(actual)) | (Ty::Pointer(elem), Ty::Pointer(actual)) => elem.infer(actual, bindings),
+            (Ty::Optional(inner), Ty::Optional(actual)) => inner.infer(actual, bindings),
+            (Ty::Optional(inner), actual) if *actual != Ty::Nil => inner.infer(actual, bindings),
+            (Ty::Tuple(elems), Ty::Tuple(actual_elems)) => {
+                for (elem, actual) in elems.iter().zip(actual_elems) {
+                    elem.infer(actual, bindings);
+                }
+            }
+            (Ty::Struct(name, args), Ty::Struct(actual_name, actual_args)) if name == actual_name => {
+                for (arg, actual) in args.iter().zip(actual_args) {
+                    arg.infer(actual, bindings);
+                }
+            }
+            (Ty::Fn(params, return_ty), Ty::Fn(actual_params, actual_return_ty)) => {
+                for (param, actual) in params.iter().zip(actual_params) {
+                    param.infer(actual, bindings);
+                }
+
+                return_ty.infer(actual_return_ty, bindings);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Int => write!(f, "int"),
+            Ty::Float => write!(f, "float"),
+            Ty::Bool => write!(f, "bool"),
+            Ty::Str => write!(f, "string"),
+            Ty::Void => write!(f, "void"),
+            Ty::Any => write!(f, "any"),
+            Ty::Array(elem, len) => write!(f, "[{elem}; {len}]"),
+            Ty::Slice(elem) => write!(f, "[{elem}]"),
+            Ty::Pointer(pointee) => write!(f, "^{pointee}"),
+            Ty::Optional(inner) => write!(f, "?{inner}"),
+            Ty::Nil => write!(f, "nil"),
+            Ty::Tuple(elems) => {
+                let elems = elems.iter().map(|elem| elem.to_string()).collect::<Vec<_>>();
+                write!(f, "({})", elems.join(", "))
+            }
+            Ty::Struct(name, args) if !args.is_empty() => {
+                let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+                write!(f, "{name}<{}>", args.join(", "))
+            }
+            Ty::Struct(name, _) | Ty::Enum(name) | Ty::Param(name) => write!(f, "{name}"),
+            Ty::Fn(params, return_ty) => {
+                let params = params.iter().map(|param| param.to_string()).collect::<Vec<_>>();
+                write!(f, "fn({})", params.join(", "))?;
+
+                match **return_ty {
+                    Ty::Void => Ok(()),
+                    ref return_ty => write!(f, " -> {return_ty}"),
+                }
+            }
+            Ty::Error => write!(f, "{{error}}"),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Span {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Span {
+    pub fn new(line: u32, column: u32) -> Self {
+        Span { line, column }
+    }
+}
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+    /// File the span points into, `None` for the file being compiled.
+    pub file: Option<String>,
+    pub notes: Vec<Note>,
+}
+
+/// Additional information attached to a diagnostic, such as where the item it's about is declared.
+#[derive(Debug)]
+pub struct Note {
+    pub message: String,
+    pub span: Span,
+    pub file: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Diagnostic {
+            message: message.into(),
+            span,
+            file: None,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_note(mut self, message: impl Into<String>, span: Span, file: Option<String>) -> Self {
+        self.notes.push(Note { message: message.into(), span, file });
+        self
+    }
+
+    pub fn in_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    pub fn print(&self, file: &str) {
+        println!("{} at {}:{}:{}", self.message, self.file.as_deref().unwrap_or(file), self.span.line, self.span.column);
+
+        for note in &self.notes {
+            println!("  note: {} at {}:{}:{}", note.message, note.file.as_deref().unwrap_or(file), note.span.line, note.span.column);
+        }
+    }
+}
@@ -36,10 +36,9 @@ impl<'a> CharsNavigator<'a> {
 
         if next.is_some() {
             self.current = next;
+            self.check_line_column();
         };
 
-        self.check_line_column();
-
         next.is_some()
     }
 
@@ -9,6 +9,8 @@ pub struct Lexer<'a> {
     file_path: &'a str,
     nav: CharsNavigator<'a>,
     keywords: HashMap<&'static str, TokenKind>,
+    /// Whether the last token was `.` or `?.`, after which a number is a tuple element and has no fraction, `t.0.1`.
+    after_dot: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -18,9 +20,10 @@ impl<'a> Lexer<'a> {
         }
 
         Lexer {
-            file_path: file_path,
+            file_path,
             nav: CharsNavigator::new(source.chars()),
             keywords: Self::init_keywords(),
+            after_dot: false,
         }
     }
 
@@ -31,6 +34,7 @@ impl<'a> Lexer<'a> {
         keywords.insert("fn", TokenKind::Fn);
         keywords.insert("return", TokenKind::Return);
         keywords.insert("let", TokenKind::Let);
+        keywords.insert("const", TokenKind::Const);
         keywords.insert("else", TokenKind::Else);
         keywords.insert("loop", TokenKind::Loop);
         keywords.insert("if", TokenKind::If);
@@ -38,6 +42,7 @@ impl<'a> Lexer<'a> {
         keywords.insert("false", TokenKind::False);
         keywords.insert("true", TokenKind::True);
         keywords.insert("for", TokenKind::For);
+        keywords.insert("in", TokenKind::In);
         keywords.insert("print", TokenKind::Print);
         keywords.insert("while", TokenKind::While);
         keywords.insert("struct", TokenKind::Struct);
@@ -53,8 +58,9 @@ impl<'a> Lexer<'a> {
         keywords.insert("switch", TokenKind::Switch);
         keywords.insert("fall", TokenKind::Fall);
         keywords.insert("defer", TokenKind::Defer);
+        keywords.insert("interface", TokenKind::Interface);
 
-        return keywords;
+        keywords
     }
 
     pub fn tokenize(&mut self) -> Vec<Token> {
@@ -62,6 +68,7 @@ impl<'a> Lexer<'a> {
 
         loop {
             let token = self.eat_token();
+            self.after_dot = matches!(token.kind, TokenKind::Dot | TokenKind::QuestionDot);
 
             if matches!(token.kind, TokenKind::Eof) {
                 break;
@@ -76,12 +83,14 @@ impl<'a> Lexer<'a> {
 
     fn eat_token(&mut self) -> Token {
         while !self.nav.is_at_end() {
+            let (line, column) = (self.nav.line(), self.nav.column());
+
             if let Some(number) = self.eat_number() {
-                return number;
+                return number.at(line, column);
             } else if let Some(string) = self.eat_string() {
-                return string;
+                return string.at(line, column);
             } else if let Some(identifier) = self.eat_identifier() {
-                return identifier;
+                return identifier.at(line, column);
             } else {
                 return match self.nav.current() {
                     Some(c) => {
@@ -109,7 +118,7 @@ impl<'a> Lexer<'a> {
                                 Token::new(TokenKind::RightBrace, self.nav.line(), Value::from("}"))
                             }
                             ';' => {
-                                Token::new(TokenKind::Semicolon, self.nav.line(), Value::from(":"))
+                                Token::new(TokenKind::Semicolon, self.nav.line(), Value::from(";"))
                             }
                             ':' => {
                                 if self.nav.next_if_match(c) {
@@ -124,11 +133,25 @@ impl<'a> Lexer<'a> {
                             }
                             '.' => {
                                 if self.nav.next_if_match(c) {
-                                    Token::new(
-                                        TokenKind::DotDot,
-                                        self.nav.line(),
-                                        Value::from(".."),
-                                    )
+                                    if self.nav.next_if_match('=') {
+                                        Token::new(
+                                            TokenKind::DotDotEqual,
+                                            self.nav.line(),
+                                            Value::from("..="),
+                                        )
+                                    } else if self.nav.next_if_match(c) {
+                                        Token::new(
+                                            TokenKind::DotDotDot,
+                                            self.nav.line(),
+                                            Value::from("..."),
+                                        )
+                                    } else {
+                                        Token::new(
+                                            TokenKind::DotDot,
+                                            self.nav.line(),
+                                            Value::from(".."),
+                                        )
+                                    }
                                 } else {
                                     Token::new(TokenKind::Dot, self.nav.line(), Value::from("."))
                                 }
@@ -140,7 +163,7 @@ impl<'a> Lexer<'a> {
                                     Token::new(
                                         TokenKind::MinusEqual,
                                         self.nav.line(),
-                                        Value::from("-+"),
+                                        Value::from("-="),
                                     )
                                 } else if self.nav.next_if_match('>') {
                                     Token::new(
@@ -201,7 +224,21 @@ impl<'a> Lexer<'a> {
                             '|' => Token::new(TokenKind::Pipe, self.nav.line(), Value::from("|")),
                             '@' => Token::new(TokenKind::At, self.nav.line(), Value::from("@")),
                             '?' => {
-                                Token::new(TokenKind::Question, self.nav.line(), Value::from("?"))
+                                if self.nav.next_if_match('.') {
+                                    Token::new(
+                                        TokenKind::QuestionDot,
+                                        self.nav.line(),
+                                        Value::from("?."),
+                                    )
+                                } else if self.nav.next_if_match(c) {
+                                    Token::new(
+                                        TokenKind::QuestionQuestion,
+                                        self.nav.line(),
+                                        Value::from("??"),
+                                    )
+                                } else {
+                                    Token::new(TokenKind::Question, self.nav.line(), Value::from("?"))
+                                }
                             }
                             '!' => {
                                 if self.nav.next_if_match('=') {
@@ -256,7 +293,7 @@ impl<'a> Lexer<'a> {
                                 self.nav.next();
                                 continue;
                             }
-                            ' ' => {
+                            ' ' | '\t' | '\r' => {
                                 self.nav.next();
                                 continue;
                             }
@@ -271,7 +308,7 @@ impl<'a> Lexer<'a> {
                         };
 
                         self.nav.next();
-                        token
+                        token.at(line, column)
                     }
                     None => Token::eof(),
                 };
@@ -286,7 +323,7 @@ impl<'a> Lexer<'a> {
         let mut exponent = String::new();
         let mut has_exponent = false;
 
-        if !self.nav.current().unwrap().is_digit(10) {
+        if !self.nav.current().unwrap().is_ascii_digit() {
             return Option::None;
         }
 
@@ -294,7 +331,7 @@ impl<'a> Lexer<'a> {
         while !self.nav.is_at_end() {
             let current = self.nav.current().unwrap();
 
-            if current.is_digit(10) {
+            if current.is_ascii_digit() {
                 mantissa.push(current);
                 self.nav.next();
             } else {
@@ -311,10 +348,10 @@ impl<'a> Lexer<'a> {
             ));
         }
 
-        let is_next_dot = matches!(self.nav.current(), Some(c) if c == '.');
-        let is_after_next_digit = matches!(self.nav.peek(), Some(c) if c.is_digit(10));
+        let is_next_dot = matches!(self.nav.current(), Some('.'));
+        let is_after_next_digit = matches!(self.nav.peek(), Some(c) if c.is_ascii_digit());
 
-        if is_next_dot && is_after_next_digit {
+        if is_next_dot && is_after_next_digit && !self.after_dot {
             has_exponent = true;
             self.nav.next();
         }
@@ -323,7 +360,7 @@ impl<'a> Lexer<'a> {
             while !self.nav.is_at_end() {
                 let current = self.nav.current().unwrap();
 
-                if current.is_digit(10) {
+                if current.is_ascii_digit() {
                     exponent.push(current);
                     self.nav.next();
                 } else {
@@ -424,6 +461,8 @@ impl<'a> Lexer<'a> {
             exit_with_err_msg("Unterminated string");
         }
 
+        self.nav.next();
+
         Option::Some(Token::new(
             TokenKind::String,
             self.nav.line(),
@@ -438,16 +477,11 @@ impl<'a> Lexer<'a> {
 
         let mut identifier = String::from(self.nav.current().unwrap());
 
-        loop {
-            match self.nav.peek() {
-                Some(c) => {
-                    if c.is_alphabetic() || *c == '_' || c.is_digit(10) {
-                        identifier.push(self.nav.next().unwrap());
-                    } else {
-                        break;
-                    }
-                }
-                None => break,
+        while let Some(c) = self.nav.peek() {
+            if c.is_alphabetic() || *c == '_' || c.is_ascii_digit() {
+                identifier.push(self.nav.next().unwrap());
+            } else {
+                break;
             }
         }
 
@@ -528,6 +562,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn eat_number_float() {
         let source = "3.14";
         let mut lexer = Lexer::new("/test.mv", source);
@@ -590,6 +625,19 @@ mod tests {
         assert_eq!(token.value, Value::Str("import".to_string()))
     }
 
+    #[test]
+    fn parse_string_and_whitespace() {
+        let source = "\"abc\";\t\r\nfoo";
+        let mut lexer = Lexer::new("/test.mv", source);
+
+        let tokens = lexer.tokenize();
+
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0].value, Value::from("abc"));
+        assert_eq!(tokens[1].kind, TokenKind::Semicolon);
+        assert_eq!(tokens[2].value, Value::from("foo"));
+    }
+
     #[test]
     fn parse_empty_fn_without_parameters() {
         let source = "fn foo() { }";
@@ -616,4 +664,33 @@ mod tests {
 
         assert_eq!(tokens.len(), 0)
     }
+
+    #[test]
+    fn parse_ranges() {
+        let source = "0..10 0..=n";
+        let mut lexer = Lexer::new("/test.mv", source);
+
+        let tokens = lexer.tokenize();
+
+        assert_eq!(tokens.len(), 6);
+        assert_eq!(tokens[0].value, Value::Int(0));
+        assert_eq!(tokens[1].kind, TokenKind::DotDot);
+        assert_eq!(tokens[2].value, Value::Int(10));
+        assert_eq!(tokens[4].kind, TokenKind::DotDotEqual);
+        assert_eq!(tokens[5].kind, TokenKind::Identifier);
+    }
+
+    #[test]
+    fn parse_tuple_fields() {
+        let source = "t.0.1 1.5";
+        let mut lexer = Lexer::new("/test.mv", source);
+
+        let tokens = lexer.tokenize();
+
+        assert_eq!(tokens.len(), 6);
+        assert_eq!(tokens[2].value, Value::Int(0));
+        assert_eq!(tokens[3].kind, TokenKind::Dot);
+        assert_eq!(tokens[4].value, Value::Int(1));
+        assert_eq!(tokens[5].value, Value::Float(1.5));
+    }
 }
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::fs;
+use std::iter;
+use std::path::{Component, Path, PathBuf};
+
+use super::ast::decl::{Decl, ImportDecl, ImportPath};
+use super::diagnostic::Diagnostic;
+use super::lexer::Lexer;
+use super::parser::decl_parser::DeclParser;
+use super::token_stream::TokenStream;
+
+/// A parsed source file. Every file is its own namespace, whose items importers reach with `name::item`.
+pub struct Module {
+    /// Prefix of the items declared in the module, empty for the root file. It's the path of the file relative to the
+    /// directory of the root file or to the search path it's in, `math::vec` for `math/vec.mv`.
+    pub name: String,
+    /// Path of the file as shown in diagnostics.
+    pub file: String,
+    pub decls: Vec<Decl>,
+    /// Imported modules by the name they're referred to with, the last segment of the import path.
+    pub imports: HashMap<String, usize>,
+}
+
+/// Loads the file being compiled and, transitively, every file it imports. Imports are resolved relative to the
+/// importing file first, then to each search path in order. A file imported from several places is loaded once.
+pub struct Loader {
+    search_paths: Vec<PathBuf>,
+    /// Canonical directories that module names are relative to, the directory of the root file then the search paths.
+    roots: Vec<PathBuf>,
+    modules: Vec<Module>,
+    /// Canonical paths of the loaded files, parallel to `modules`.
+    paths: Vec<PathBuf>,
+    /// Modules whose imports are being loaded, from the root file down, to detect cycles.
+    stack: Vec<usize>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Loader {
+    pub fn new(search_paths: Vec<PathBuf>) -> Self {
+        Loader {
+            search_paths,
+            roots: Vec::new(),
+            modules: Vec::new(),
+            paths: Vec::new(),
+            stack: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Returns the modules, the root file first, along with the syntax and import errors of all of them.
+    pub fn load(mut self, file_path: &str, source: &str) -> (Vec<Module>, Vec<Diagnostic>) {
+        let path = fs::canonicalize(file_path).unwrap_or_else(|_| PathBuf::from(file_path));
+
+        let search_paths = self.search_paths.iter().map(|dir| fs::canonicalize(dir).unwrap_or_else(|_| dir.clone()));
+
+        self.roots = path.parent().map(PathBuf::from).into_iter().chain(search_paths).collect();
+        self.load_module(String::new(), file_path.to_string(), path, source);
+
+        (self.modules, self.diagnostics)
+    }
+
+    fn load_module(&mut self, name: String, file: String, path: PathBuf, source: &str) -> usize {
+        let index = self.modules.len();
+        let decls = self.parse(&file, source);
+
+        self.modules.push(Module {
+            name,
+            file,
+            decls: Vec::new(),
+            imports: HashMap::new(),
+        });
+        self.paths.push(path);
+        self.stack.push(index);
+
+        let mut imports = HashMap::<String, usize>::new();
+
+        for decl in &decls {
+            let Decl::Import(import) = decl else { continue };
+            let Some((name, module)) = self.load_import(index, import) else { continue };
+
+            if imports.insert(name.clone(), module).is_some() {
+                self.error(index, format!("module '{name}' is already imported"), import);
+            }
+        }
+
+        self.stack.pop();
+
+        let module = &mut self.modules[index];
+        module.decls = decls;
+        module.imports = imports;
+
+        index
+    }
+
+    fn parse(&mut self, file: &str, source: &str) -> Vec<Decl> {
+        // The lexer rejects empty sources, but an empty module is fine.
+        if source.is_empty() {
+            return Vec::new();
+        }
+
+        let mut stream = TokenStream::new(Lexer::new(file, source).tokenize());
+        let decls = DeclParser::new(&mut stream).parse();
+
+        self.diagnostics.extend(stream.take_diagnostics().into_iter().map(|diagnostic| diagnostic.in_file(file)));
+
+        decls
+    }
+
+    /// Loads the module of an import unless it's already loaded, and returns its name and index.
+    fn load_import(&mut self, importer: usize, import: &ImportDecl) -> Option<(String, usize)> {
+        let relative = match &import.path {
+            ImportPath::File(path) => PathBuf::from(path),
+            ImportPath::Module(segments) => segments.iter().collect(),
+        };
+
+        let relative = if relative.extension().is_none() { relative.with_extension("mv") } else { relative };
+        let importer_dir = self.paths[importer].parent().map(PathBuf::from).unwrap_or_default();
+
+        let Some(found) = iter::once(&importer_dir).chain(&self.search_paths).map(|dir| dir.join(&relative)).find(|path| path.is_file()) else {
+            self.error(importer, format!("cannot find module '{}'", import.path), import);
+            return None;
+        };
+
+        let path = fs::canonicalize(&found).unwrap_or_else(|_| found.clone());
+        let file = found.display().to_string();
+
+        if let Some(start) = self.stack.iter().position(|module| self.paths[*module] == path) {
+            let chain = self.stack[start..]
+                .iter()
+                .map(|module| self.modules[*module].file.as_str())
+                .chain(iter::once(file.as_str()))
+                .collect::<Vec<_>>();
+            self.error(importer, format!("import cycle: {}", chain.join(" -> ")), import);
+            return None;
+        }
+
+        // Importers refer to the module by the last segment of the import path, whatever the module's name.
+        let alias = relative.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+
+        if let Some(module) = self.paths.iter().position(|other| *other == path) {
+            return Some((alias, module));
+        }
+
+        let name = self.module_name(&path);
+
+        if let Some(other) = self.modules.iter().find(|module| module.name == name) {
+            let message = format!("module name '{name}' is already used by '{}'", other.file);
+            self.error(importer, message, import);
+            return None;
+        }
+
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(e) => {
+                self.error(importer, format!("cannot read module '{}': {e}", import.path), import);
+                return None;
+            }
+        };
+
+        Some((alias, self.load_module(name, file, path, &source)))
+    }
+
+    /// Path of the file relative to the first root it's in, without the extension and with `::` between directories.
+    /// A file outside of the roots is named by its full path. Two files can only get the same name if they're at the
+    /// same place in different roots.
+    fn module_name(&self, path: &Path) -> String {
+        let relative = self.roots.iter().find_map(|root| path.strip_prefix(root).ok()).unwrap_or(path).with_extension("");
+        let segments = relative.components().filter_map(|component| match component {
+            Component::Normal(segment) => Some(segment.to_string_lossy().into_owned()),
+            _ => None,
+        });
+
+        segments.collect::<Vec<_>>().join("::")
+    }
+
+    fn error(&mut self, module: usize, message: String, import: &ImportDecl) {
+        let file = self.modules[module].file.clone();
+        self.diagnostics.push(Diagnostic::error(message, import.span).in_file(file));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the files into a fresh directory named after the test and loads `main.mv` from it.
+    fn load(test: &str, files: &[(&str, &str)]) -> (Vec<Module>, Vec<String>) {
+        let dir = std::env::temp_dir().join(format!("mova-loader-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        for (name, source) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+
+        let main = dir.join("main.mv");
+        let source = fs::read_to_string(&main).unwrap();
+        let (modules, diagnostics) = Loader::new(vec![dir.join("lib")]).load(main.to_str().unwrap(), &source);
+
+        let dir = dir.to_string_lossy().into_owned();
+        let errors = diagnostics.into_iter().map(|diagnostic| diagnostic.message.replace(&dir, "")).collect();
+
+        (modules, errors)
+    }
+
+    #[test]
+    fn loads_imports_relative_to_importer_and_search_path() {
+        let (modules, errors) = load(
+            "relative",
+            &[
+                ("main.mv", "import math::vec; import \"util\"; fn main() {}"),
+                ("math/vec.mv", "import \"../util.mv\"; fn len() {}"),
+                ("lib/util.mv", "fn helper() {}"),
+                ("util.mv", "fn shared() {}"),
+            ],
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(modules.iter().map(|module| module.name.as_str()).collect::<Vec<_>>(), vec!["", "math::vec", "util"]);
+        assert_eq!(modules[0].imports["vec"], 1);
+        assert_eq!(modules[1].imports["util"], 2);
+        assert_eq!(modules[0].imports["util"], 2);
+        assert!(Path::new(&modules[2].file).ends_with("math/../util.mv"));
+    }
+
+    #[test]
+    fn names_modules_by_their_path() {
+        let (modules, errors) = load(
+            "names",
+            &[
+                ("main.mv", "import a; import b; fn main() {}"),
+                ("a.mv", "import math::vec;"),
+                ("b.mv", "import physics::vec;"),
+                ("math/vec.mv", "fn len() {}"),
+                ("physics/vec.mv", "fn len() {}"),
+            ],
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(modules.iter().map(|module| module.name.as_str()).collect::<Vec<_>>(), vec!["", "a", "math::vec", "b", "physics::vec"]);
+        assert_eq!(modules[1].imports["vec"], 2);
+        assert_eq!(modules[3].imports["vec"], 4);
+    }
+
+    #[test]
+    fn reports_import_cycles_with_the_chain() {
+        let (_, errors) = load("cycle", &[("main.mv", "import a;"), ("a.mv", "import b;"), ("b.mv", "import a;")]);
+
+        assert_eq!(errors, vec!["import cycle: /a.mv -> /b.mv -> /a.mv"]);
+    }
+
+    #[test]
+    fn reports_missing_modules_and_syntax_errors() {
+        let (modules, errors) = load("missing", &[("main.mv", "import nope::mod; import bad;"), ("bad.mv", "fn broken( {}")]);
+
+        assert_eq!(modules.len(), 2);
+        assert_eq!(errors, vec!["cannot find module 'nope::mod'", "expected 'let' or 'var' before parameter name, found '{'"]);
+    }
+}
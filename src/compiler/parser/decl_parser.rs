@@ -1,11 +1,16 @@
-use crate::compiler::ast::decl::{Decl, FnDecl, FnParam, LetDecl, VarDecl};
-use crate::compiler::ast::expr::Expr;
+use crate::compiler::ast::decl::{
+    Attribute, BindingTarget, ConstDecl, Decl, EnumDecl, EnumVariant, FnDecl, FnParam, ImplDecl, ImportDecl, ImportPath, InterfaceDecl, LetDecl, StructDecl, StructField, TypeParam, VarDecl,
+    VariantPayload,
+};
+use crate::compiler::ast::expr::{Expr, Path};
+use crate::compiler::ast::type_info::TypeInfo;
 use crate::compiler::token::{TokenKind, Value};
 use crate::compiler::token_stream::TokenStream;
-use crate::compiler::utils::compare_token_kind;
 
 use super::expr_parser::ExprParser;
 use super::stmt_parser::StmtParser;
+use super::type_parser::TypeParser;
+use super::ParseResult;
 
 pub struct DeclParser<'a> {
     stream: &'a mut TokenStream,
@@ -13,107 +18,1317 @@ pub struct DeclParser<'a> {
 
 impl<'a> DeclParser<'a> {
     pub fn new(stream: &'a mut TokenStream) -> Self {
-        DeclParser { stream: stream }
+        DeclParser { stream }
     }
 
+    /// Parses the whole stream. Malformed declarations are replaced with `Decl::Error` and the parser resumes at the
+    /// next declaration, so the diagnostics recorded in the stream cover every syntax error in the file.
     pub fn parse(&mut self) -> Vec<Decl> {
         let mut decls = Vec::<Decl>::new();
 
         while !self.stream.is_at_end() {
-            let current = self.stream.current();
-
-            if self.stream.check(current, TokenKind::Var) {
-                decls.push(self.parse_var().expect("expected var declaration"))
-            } else if self.stream.check(current, TokenKind::Let) {
-                decls.push(self.parse_let().expect("expected let declaration"))
-            } else if self.stream.check(current, TokenKind::Fn) {
-                decls.push(self.parse_fn().expect("expected fn declaration"))
-            } else {
-                self.stream.next();
+            let start = self.stream.position();
+
+            match self.parse_decl() {
+                Ok(decl) => decls.push(decl),
+                Err(_) => {
+                    decls.push(Decl::Error);
+                    self.synchronize(start);
+                }
             }
         }
 
         decls
     }
 
-    fn parse_var(&mut self) -> Option<Decl> {
-        let name = self.stream.next_expected(TokenKind::Identifier, "expected variable name").clone();
+    fn parse_decl(&mut self) -> ParseResult<Decl> {
+        match self.stream.current().kind {
+            TokenKind::Var => self.parse_var().map(Decl::Var),
+            TokenKind::Let => self.parse_let().map(Decl::Let),
+            TokenKind::Const => self.parse_const(),
+            TokenKind::Fn => self.parse_fn().map(Decl::Fn),
+            TokenKind::Struct => self.parse_struct(false),
+            TokenKind::Enum => self.parse_enum(false),
+            TokenKind::Impl => self.parse_impl(),
+            TokenKind::Interface => self.parse_interface(false),
+            TokenKind::Import => self.parse_import(),
+            TokenKind::Internal => self.parse_internal(),
+            TokenKind::Sharp => self.parse_attributed(),
+            _ => Err(self.stream.error_at_current("expected declaration")),
+        }
+    }
 
-        self.stream.next_expected(TokenKind::Colon, "expected ':' after variable declaration");
+    /// Parses a declaration preceded by attributes, which only functions and structs can have.
+    fn parse_attributed(&mut self) -> ParseResult<Decl> {
+        let attrs = self.parse_attributes()?;
+        let span = attrs[0].span;
+        let mut decl = self.parse_decl()?;
 
-        if self.stream.advance_if_match(TokenKind::Equal) {
-            let expr = ExprParser::new(self.stream).parse().expect("failed parsing expression");
+        match &mut decl {
+            Decl::Fn(fn_decl) => fn_decl.attrs = attrs,
+            Decl::Struct(struct_decl) => struct_decl.attrs = attrs,
+            _ => {
+                self.stream.error("attributes are only allowed on functions, structs, fields and statements", span);
+            }
+        }
 
-            self.expect_semicolor();
+        Ok(decl)
+    }
 
-            return Some(Decl::Var(VarDecl {
-                name: name.value.get_str(),
-                expr: expr,
-            }));
+    /// Parses any number of `#[name]` or `#[name(args, ...)]` attributes.
+    pub fn parse_attributes(&mut self) -> ParseResult<Vec<Attribute>> {
+        let mut attrs = Vec::<Attribute>::new();
+
+        while self.stream.check_current(TokenKind::Sharp) {
+            let span = self.stream.next().unwrap().span();
+
+            self.stream.next_expected(TokenKind::LeftBracket, "expected '[' after '#'")?;
+
+            let name = self.stream.next_expected(TokenKind::Identifier, "expected attribute name")?.value.get_str();
+            let mut args = Vec::<Expr>::new();
+
+            if self.stream.advance_if_match(TokenKind::LeftParen) {
+                while !self.stream.check_current(TokenKind::RightParen) {
+                    args.push(ExprParser::new(self.stream).parse()?);
+
+                    if !self.stream.advance_if_match(TokenKind::Comma) {
+                        break;
+                    }
+                }
+
+                self.stream.next_expected(TokenKind::RightParen, "expected ')' after attribute arguments")?;
+            }
+
+            self.stream.next_expected(TokenKind::RightBracket, "expected ']' after attribute")?;
+
+            attrs.push(Attribute { name, args, span });
+        }
+
+        Ok(attrs)
+    }
+
+    pub fn parse_var(&mut self) -> ParseResult<VarDecl> {
+        let span = self.stream.next_expected(TokenKind::Var, "expected 'var'")?.span();
+        let (target, type_info, expr) = self.parse_binding()?;
+
+        Ok(VarDecl {
+            target,
+            type_info,
+            expr,
+            is_internal: false,
+            span,
+        })
+    }
+
+    pub fn parse_let(&mut self) -> ParseResult<LetDecl> {
+        let span = self.stream.next_expected(TokenKind::Let, "expected 'let'")?.span();
+        let (target, type_info, expr) = self.parse_binding()?;
+
+        Ok(LetDecl {
+            target,
+            type_info,
+            expr,
+            is_internal: false,
+            span,
+        })
+    }
+
+    /// Parses `const NAME: type = expr;` or a `const fn`.
+    fn parse_const(&mut self) -> ParseResult<Decl> {
+        let span = self.stream.next_expected(TokenKind::Const, "expected 'const'")?.span();
+
+        if self.stream.check_current(TokenKind::Fn) {
+            return Ok(Decl::Fn(FnDecl { is_const: true, ..self.parse_fn()? }));
         }
 
-        self.expect_semicolor();
+        let name = self.stream.next_expected(TokenKind::Identifier, "expected constant name")?.value.get_str();
+
+        self.stream.next_expected(TokenKind::Colon, "expected ':' after constant name")?;
+        let type_info = TypeParser::new(self.stream).parse()?;
+
+        self.stream.next_expected(TokenKind::Equal, "expected '=' and a value after constant type")?;
+        let expr = ExprParser::new(self.stream).parse()?;
 
-        None
+        self.expect_semicolor()?;
+
+        Ok(Decl::Const(ConstDecl {
+            name,
+            type_info,
+            expr,
+            is_internal: false,
+            span,
+        }))
     }
 
-    fn parse_let(&mut self) -> Option<Decl> {
-        let name = self.stream.next_expected(TokenKind::Identifier, "expected variable name").clone();
+    /// Parses `name: type = expr;` where either the type or the initializer can be omitted, but not both. The name can
+    /// be a tuple of names `(a, b)`.
+    fn parse_binding(&mut self) -> ParseResult<(BindingTarget, Option<TypeInfo>, Option<Expr>)> {
+        let target = if self.stream.check_current(TokenKind::LeftParen) {
+            self.parse_tuple_target()?
+        } else {
+            BindingTarget::Name(self.stream.next_expected(TokenKind::Identifier, "expected variable name")?.value.get_str())
+        };
 
-        self.stream.next_expected(TokenKind::Colon, "expected ':' after variable declaration");
+        self.stream.next_expected(TokenKind::Colon, "expected ':' after variable name")?;
 
-        if self.stream.advance_if_match(TokenKind::Equal) {
-            let expr = ExprParser::new(self.stream).parse().expect("failed parsing expression");
+        let type_info = if self.stream.check_current(TokenKind::Equal) {
+            None
+        } else {
+            Some(TypeParser::new(self.stream).parse()?)
+        };
 
-            self.expect_semicolor();
+        let expr = if self.stream.advance_if_match(TokenKind::Equal) {
+            Some(ExprParser::new(self.stream).parse()?)
+        } else {
+            None
+        };
 
-            return Some(Decl::Let(LetDecl {
-                name: name.value.get_str(),
-                expr: expr,
-            }));
+        self.expect_semicolor()?;
+
+        Ok((target, type_info, expr))
+    }
+
+    /// Parses `(a, b, ...)`, the names bound to the elements of a tuple.
+    fn parse_tuple_target(&mut self) -> ParseResult<BindingTarget> {
+        let span = self.stream.next_expected(TokenKind::LeftParen, "expected '('")?.span();
+        let mut names = Vec::<String>::new();
+
+        while !self.stream.check_current(TokenKind::RightParen) {
+            names.push(self.stream.next_expected(TokenKind::Identifier, "expected variable name")?.value.get_str());
+
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
+            }
         }
 
-        self.expect_semicolor();
+        self.stream.next_expected(TokenKind::RightParen, "expected ')' after variable names")?;
+
+        if names.len() < 2 {
+            return Err(self.stream.error("a tuple needs at least two elements", span));
+        }
 
-        None
+        Ok(BindingTarget::Tuple(names))
     }
 
-    fn parse_fn(&mut self) -> Option<Decl> {
-        let name = self.stream.next_expected(TokenKind::Identifier, "expected function name").clone();
+    fn parse_fn(&mut self) -> ParseResult<FnDecl> {
+        let fn_decl = self.parse_fn_sig()?;
+        let stmts = StmtParser::new(self.stream).parse()?;
+
+        Ok(FnDecl { stmts, ..fn_decl })
+    }
 
-        self.stream.next_expected(TokenKind::LeftParen, "expected '(' after function name");
+    /// Parses a function up to its body, leaving the body empty.
+    fn parse_fn_sig(&mut self) -> ParseResult<FnDecl> {
+        let span = self.stream.next_expected(TokenKind::Fn, "expected 'fn'")?.span();
+        let name = self.stream.next_expected(TokenKind::Identifier, "expected function name")?.value.get_str();
+        let type_params = self.parse_type_params()?;
 
+        self.stream.next_expected(TokenKind::LeftParen, "expected '(' after function name")?;
+
+        let params = self.parse_fn_params()?;
+
+        let return_type = if self.stream.advance_if_match(TokenKind::MinusGreater) {
+            Some(TypeParser::new(self.stream).parse()?)
+        } else {
+            None
+        };
+
+        Ok(FnDecl {
+            attrs: Vec::new(),
+            name,
+            type_params,
+            params,
+            return_type,
+            stmts: Vec::new(),
+            is_internal: false,
+            is_const: false,
+            span,
+        })
+    }
+
+    /// Parses `init(params) { ... }`, which never declares a return type.
+    fn parse_init(&mut self) -> ParseResult<FnDecl> {
+        let span = self.stream.next_expected(TokenKind::Init, "expected 'init'")?.span();
+
+        self.stream.next_expected(TokenKind::LeftParen, "expected '(' after 'init'")?;
+
+        let params = self.parse_fn_params()?;
+        let stmts = StmtParser::new(self.stream).parse()?;
+
+        Ok(FnDecl {
+            attrs: Vec::new(),
+            name: "init".to_string(),
+            type_params: Vec::new(),
+            params,
+            return_type: None,
+            stmts,
+            is_internal: false,
+            is_const: false,
+            span,
+        })
+    }
+
+    /// Parses `<T, U: Bound + ...>` after the name of a generic item, if present.
+    fn parse_type_params(&mut self) -> ParseResult<Vec<TypeParam>> {
+        let mut type_params = Vec::<TypeParam>::new();
+
+        if !self.stream.advance_if_match(TokenKind::Less) {
+            return Ok(type_params);
+        }
+
+        while !self.stream.check_current(TokenKind::Greater) {
+            let name = self.stream.next_expected(TokenKind::Identifier, "expected type parameter name")?.clone();
+            let mut bounds = Vec::<Path>::new();
+
+            if self.stream.advance_if_match(TokenKind::Colon) {
+                loop {
+                    bounds.push(self.parse_path("expected interface name")?);
+
+                    if !self.stream.advance_if_match(TokenKind::Plus) {
+                        break;
+                    }
+                }
+            }
+
+            type_params.push(TypeParam {
+                name: name.value.get_str(),
+                bounds,
+                span: name.span(),
+            });
+
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        self.stream.next_expected(TokenKind::Greater, "expected '>' after type parameters")?;
+
+        Ok(type_params)
+    }
+
+    /// Parses the parameters after `(`, up to and including `)`.
+    fn parse_fn_params(&mut self) -> ParseResult<Vec<FnParam>> {
         let mut fn_params = Vec::<FnParam>::new();
 
-        while !compare_token_kind(self.stream.peek(), TokenKind::RightParen) {
-            let next = self.stream.next().unwrap();
+        while !self.stream.check_current(TokenKind::RightParen) {
+            fn_params.push(self.parse_fn_param()?);
 
-            match next.kind {
-                TokenKind::Let => fn_params.push(FnParam {
-                    name: self.stream.next_expected(TokenKind::Identifier, "expected identifier after 'let' keyword").value.get_str(),
-                    is_mutable: false,
-                }),
-                TokenKind::Var => fn_params.push(FnParam {
-                    name: self.stream.next_expected(TokenKind::Identifier, "expected identifier after 'var' keyword").value.get_str(),
-                    is_mutable: true,
-                }),
-                _ => continue,
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
             }
         }
 
-        self.stream.next_expected(TokenKind::RightParen, "expected ')' after parameters");
-        // TODO add checking function return type
+        self.stream.next_expected(TokenKind::RightParen, "expected ')' after parameters")?;
+
+        Ok(fn_params)
+    }
+
+    pub fn parse_fn_param(&mut self) -> ParseResult<FnParam> {
+        let current = self.stream.current();
+
+        if current.kind == TokenKind::Identifier && current.value == Value::from("self") {
+            let span = self.stream.next().unwrap().span();
+
+            return Ok(FnParam {
+                name: "self".to_string(),
+                type_info: None,
+                is_mutable: false,
+                default: None,
+                is_variadic: false,
+                span,
+            });
+        }
+
+        let is_mutable = match self.stream.current().kind {
+            TokenKind::Let => false,
+            TokenKind::Var => true,
+            _ => return Err(self.stream.error_at_current("expected 'let' or 'var' before parameter name")),
+        };
+
+        self.stream.next();
 
-        let stmts = StmtParser::new(self.stream).parse();
+        let name = self.stream.next_expected(TokenKind::Identifier, "expected parameter name")?.clone();
 
-        Some(Decl::Fn(FnDecl {
+        let mut is_variadic = false;
+
+        let type_info = if self.stream.advance_if_match(TokenKind::Colon) {
+            is_variadic = self.stream.advance_if_match(TokenKind::DotDotDot);
+            Some(TypeParser::new(self.stream).parse()?)
+        } else {
+            None
+        };
+
+        let default = if self.stream.advance_if_match(TokenKind::Equal) {
+            Some(ExprParser::new(self.stream).parse()?)
+        } else {
+            None
+        };
+
+        Ok(FnParam {
             name: name.value.get_str(),
-            params: fn_params,
-            stmts: stmts,
+            type_info,
+            is_mutable,
+            default,
+            is_variadic,
+            span: name.span(),
+        })
+    }
+
+    /// Parses `name` or `module::name`.
+    fn parse_path(&mut self, message: &str) -> ParseResult<Path> {
+        let mut segments = vec![self.stream.next_expected(TokenKind::Identifier, message)?.value.get_str()];
+
+        while self.stream.advance_if_match(TokenKind::ColonColon) {
+            segments.push(self.stream.next_expected(TokenKind::Identifier, "expected name after '::'")?.value.get_str());
+        }
+
+        Ok(Path { segments })
+    }
+
+    /// Parses `impl Type { ... }` or `impl Interface for Type { ... }`.
+    fn parse_impl(&mut self) -> ParseResult<Decl> {
+        let span = self.stream.next_expected(TokenKind::Impl, "expected 'impl'")?.span();
+        let mut path = self.parse_path("expected type name after 'impl'")?;
+
+        let (interface, type_name) = if self.stream.advance_if_match(TokenKind::For) {
+            (Some(path), self.stream.next_expected(TokenKind::Identifier, "expected type name after 'for'")?.value.get_str())
+        } else if path.segments.len() == 1 {
+            (None, path.segments.remove(0))
+        } else {
+            return Err(self.stream.error_at_current("expected 'for' after interface name"));
+        };
+
+        let type_params = self.parse_type_params()?;
+
+        self.stream.next_expected(TokenKind::LeftBrace, "expected '{' after type name")?;
+
+        let mut fns = Vec::<FnDecl>::new();
+
+        while !self.stream.check_current(TokenKind::RightBrace) && !self.stream.is_at_end() {
+            let attrs = self.parse_attributes()?;
+
+            let fn_decl = match self.stream.current().kind {
+                TokenKind::Fn => self.parse_fn()?,
+                TokenKind::Init => self.parse_init()?,
+                TokenKind::Internal if self.stream.peek().is_some_and(|token| token.kind == TokenKind::Fn) => {
+                    self.stream.next();
+                    FnDecl {
+                        is_internal: true,
+                        ..self.parse_fn()?
+                    }
+                }
+                _ => return Err(self.stream.error_at_current("expected 'fn', 'internal fn' or 'init' in impl block")),
+            };
+
+            fns.push(FnDecl { attrs, ..fn_decl });
+        }
+
+        self.stream.next_expected(TokenKind::RightBrace, "expected '}' at the end of impl block")?;
+
+        Ok(Decl::Impl(ImplDecl {
+            interface,
+            type_name,
+            type_params,
+            fns,
+            span,
+        }))
+    }
+
+    /// Parses `interface Name { fn name(params) [-> type]; ... }`.
+    fn parse_interface(&mut self, is_internal: bool) -> ParseResult<Decl> {
+        let span = self.stream.next_expected(TokenKind::Interface, "expected 'interface'")?.span();
+        let name = self.stream.next_expected(TokenKind::Identifier, "expected interface name")?.value.get_str();
+
+        self.stream.next_expected(TokenKind::LeftBrace, "expected '{' after interface name")?;
+
+        let mut methods = Vec::<FnDecl>::new();
+
+        while !self.stream.check_current(TokenKind::RightBrace) && !self.stream.is_at_end() {
+            methods.push(self.parse_fn_sig()?);
+            self.expect_semicolor()?;
+        }
+
+        self.stream.next_expected(TokenKind::RightBrace, "expected '}' at the end of interface")?;
+
+        Ok(Decl::Interface(InterfaceDecl { name, methods, is_internal, span }))
+    }
+
+    /// Parses `import "path/to/mod";` or `import math::vec;`.
+    fn parse_import(&mut self) -> ParseResult<Decl> {
+        let span = self.stream.next_expected(TokenKind::Import, "expected 'import'")?.span();
+
+        let path = match (self.stream.current().kind, self.stream.current().value.clone()) {
+            (TokenKind::String, Value::Str(path)) => {
+                self.stream.next();
+                ImportPath::File(path)
+            }
+            (TokenKind::Identifier, Value::Str(name)) => {
+                self.stream.next();
+
+                let mut segments = vec![name];
+
+                while self.stream.advance_if_match(TokenKind::ColonColon) {
+                    segments.push(self.stream.next_expected(TokenKind::Identifier, "expected module name after '::'")?.value.get_str());
+                }
+
+                ImportPath::Module(segments)
+            }
+            _ => return Err(self.stream.error_at_current("expected module path after 'import'")),
+        };
+
+        self.expect_semicolor()?;
+
+        Ok(Decl::Import(ImportDecl { path, span }))
+    }
+
+    fn parse_internal(&mut self) -> ParseResult<Decl> {
+        self.stream.next_expected(TokenKind::Internal, "expected 'internal'")?;
+
+        match self.stream.current().kind {
+            TokenKind::Var => Ok(Decl::Var(VarDecl {
+                is_internal: true,
+                ..self.parse_var()?
+            })),
+            TokenKind::Let => Ok(Decl::Let(LetDecl {
+                is_internal: true,
+                ..self.parse_let()?
+            })),
+            TokenKind::Const => match self.parse_const()? {
+                Decl::Const(const_decl) => Ok(Decl::Const(ConstDecl { is_internal: true, ..const_decl })),
+                Decl::Fn(fn_decl) => Ok(Decl::Fn(FnDecl { is_internal: true, ..fn_decl })),
+                _ => unreachable!(),
+            },
+            TokenKind::Fn => Ok(Decl::Fn(FnDecl {
+                is_internal: true,
+                ..self.parse_fn()?
+            })),
+            TokenKind::Struct => self.parse_struct(true),
+            TokenKind::Enum => self.parse_enum(true),
+            TokenKind::Interface => self.parse_interface(true),
+            _ => Err(self.stream.error_at_current("expected declaration after 'internal'")),
+        }
+    }
+
+    /// Parses `struct Name[<T, ...>] { [internal] field: type [= default], ... }`.
+    fn parse_struct(&mut self, is_internal: bool) -> ParseResult<Decl> {
+        let span = self.stream.next_expected(TokenKind::Struct, "expected 'struct'")?.span();
+        let name = self.stream.next_expected(TokenKind::Identifier, "expected struct name")?.value.get_str();
+        let type_params = self.parse_type_params()?;
+
+        self.stream.next_expected(TokenKind::LeftBrace, "expected '{' after struct name")?;
+
+        let fields = self.parse_fields()?;
+
+        self.stream.next_expected(TokenKind::RightBrace, "expected '}' at the end of struct")?;
+
+        Ok(Decl::Struct(StructDecl {
+            attrs: Vec::new(),
+            name,
+            type_params,
+            fields,
+            is_internal,
+            span,
         }))
     }
 
-    fn expect_semicolor(&mut self) {
-        self.stream.next_expected(TokenKind::Semicolon, "expected ';' at the end of declaration");
+    /// Parses `enum Name { Variant [= value], Variant(type, ...), Variant { field: type, ... }, ... }`.
+    fn parse_enum(&mut self, is_internal: bool) -> ParseResult<Decl> {
+        let span = self.stream.next_expected(TokenKind::Enum, "expected 'enum'")?.span();
+        let name = self.stream.next_expected(TokenKind::Identifier, "expected enum name")?.value.get_str();
+
+        self.stream.next_expected(TokenKind::LeftBrace, "expected '{' after enum name")?;
+
+        let mut variants = Vec::<EnumVariant>::new();
+
+        while !self.stream.check_current(TokenKind::RightBrace) {
+            let variant = self.stream.next_expected(TokenKind::Identifier, "expected variant name")?.clone();
+
+            let payload = if self.stream.advance_if_match(TokenKind::LeftParen) {
+                let mut types = Vec::<TypeInfo>::new();
+
+                while !self.stream.check_current(TokenKind::RightParen) {
+                    types.push(TypeParser::new(self.stream).parse()?);
+
+                    if !self.stream.advance_if_match(TokenKind::Comma) {
+                        break;
+                    }
+                }
+
+                self.stream.next_expected(TokenKind::RightParen, "expected ')' after variant types")?;
+                VariantPayload::Tuple(types)
+            } else if self.stream.advance_if_match(TokenKind::LeftBrace) {
+                let fields = self.parse_fields()?;
+                self.stream.next_expected(TokenKind::RightBrace, "expected '}' after variant fields")?;
+                VariantPayload::Struct(fields)
+            } else {
+                VariantPayload::Unit
+            };
+
+            let discriminant = if self.stream.advance_if_match(TokenKind::Equal) {
+                Some(ExprParser::new(self.stream).parse()?)
+            } else {
+                None
+            };
+
+            variants.push(EnumVariant {
+                name: variant.value.get_str(),
+                payload,
+                discriminant,
+                span: variant.span(),
+            });
+
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        self.stream.next_expected(TokenKind::RightBrace, "expected '}' at the end of enum")?;
+
+        Ok(Decl::Enum(EnumDecl { name, variants, is_internal, span }))
+    }
+
+    /// Parses the comma-separated `[#[attr]] [internal] field: type [= default]` list of a struct or a struct-like variant, up
+    /// to the closing `}`.
+    fn parse_fields(&mut self) -> ParseResult<Vec<StructField>> {
+        let mut fields = Vec::<StructField>::new();
+
+        while !self.stream.check_current(TokenKind::RightBrace) {
+            let attrs = self.parse_attributes()?;
+            let is_field_internal = self.stream.advance_if_match(TokenKind::Internal);
+            let field = self.stream.next_expected(TokenKind::Identifier, "expected field name")?.clone();
+
+            self.stream.next_expected(TokenKind::Colon, "expected ':' after field name")?;
+
+            let type_info = TypeParser::new(self.stream).parse()?;
+
+            let default = if self.stream.advance_if_match(TokenKind::Equal) {
+                Some(ExprParser::new(self.stream).parse()?)
+            } else {
+                None
+            };
+
+            fields.push(StructField {
+                attrs,
+                name: field.value.get_str(),
+                type_info,
+                default,
+                is_internal: is_field_internal,
+                span: field.span(),
+            });
+
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        Ok(fields)
+    }
+
+    fn expect_semicolor(&mut self) -> ParseResult<()> {
+        self.stream.next_expected(TokenKind::Semicolon, "expected ';' at the end of declaration")?;
+        Ok(())
+    }
+
+    /// Skips tokens up to the next declaration keyword, past the next `;`, or past the `}` closing the block the
+    /// malformed declaration was in the middle of. Always consumes at least one token.
+    fn synchronize(&mut self, start: usize) {
+        let mut depth = 0;
+
+        while !self.stream.is_at_end() {
+            let kind = self.stream.current().kind;
+
+            if depth == 0 && kind.starts_decl() && self.stream.position() > start {
+                return;
+            }
+
+            self.stream.next();
+
+            match kind {
+                TokenKind::Semicolon if depth == 0 => return,
+                TokenKind::LeftBrace => depth += 1,
+                TokenKind::RightBrace if depth <= 1 => return,
+                TokenKind::RightBrace => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::ast::expr::{Arg, BinaryOp, ClosureBody, ExprKind, UnaryOp, UpdateOp};
+    use crate::compiler::ast::pattern::{Pattern, PatternKind};
+    use crate::compiler::ast::stmt::{BreakStmt, BreakTarget, ContinueStmt, ForIter, IfStmt, ReturnStmt, Stmt};
+    use crate::compiler::ast::type_info::TypeKind;
+    use crate::compiler::lexer::Lexer;
+
+    use super::*;
+
+    fn parse(source: &str) -> Vec<Decl> {
+        let (decls, errors) = parse_with_errors(source);
+        assert!(errors.is_empty(), "{errors:?}");
+
+        decls
+    }
+
+    fn parse_with_errors(source: &str) -> (Vec<Decl>, Vec<String>) {
+        let tokens = Lexer::new("/test.mv", source).tokenize();
+        let mut stream = TokenStream::new(tokens);
+        let decls = DeclParser::new(&mut stream).parse();
+        let errors = stream.take_diagnostics().into_iter().map(|diagnostic| diagnostic.message).collect();
+
+        (decls, errors)
+    }
+
+    #[test]
+    fn parse_fn_with_return_type() {
+        let decls = parse("fn add(let a: int, let b: int) -> int { return a + b; }");
+
+        let Decl::Fn(fn_decl) = &decls[0] else { panic!("expected fn declaration") };
+
+        assert_eq!(fn_decl.name, "add");
+        assert_eq!(fn_decl.params.len(), 2);
+        assert!(matches!(&fn_decl.return_type, Some(TypeInfo { kind: TypeKind::Base(name), .. }) if name == "int"));
+        assert_eq!(fn_decl.stmts.len(), 1);
+    }
+
+    #[test]
+    fn parse_fn_without_return_type() {
+        let decls = parse("fn foo() {} fn bar(var d, let x) {}");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+        let Decl::Fn(bar) = &decls[1] else { panic!("expected fn declaration") };
+
+        assert!(foo.return_type.is_none());
+        assert!(bar.return_type.is_none());
+        assert!(bar.params[0].is_mutable);
+        assert!(!bar.params[1].is_mutable);
+    }
+
+    #[test]
+    fn parse_globals() {
+        let decls = parse("var a: int = 1; let b := 2.5; var c: string;");
+
+        assert!(matches!(
+            &decls[0],
+            Decl::Var(VarDecl {
+                type_info: Some(_),
+                expr: Some(_),
+                ..
+            })
+        ));
+        assert!(matches!(&decls[1], Decl::Let(LetDecl { type_info: None, expr: Some(_), .. })));
+        assert!(matches!(&decls[2], Decl::Var(VarDecl { type_info: Some(_), expr: None, .. })));
+    }
+
+    #[test]
+    fn reports_stray_tokens_instead_of_skipping_them() {
+        let (decls, errors) = parse_with_errors("} fn foo() {}");
+
+        assert_eq!(errors, vec!["expected declaration, found '}'"]);
+        assert!(matches!(decls[0], Decl::Error));
+        assert!(matches!(&decls[1], Decl::Fn(fn_decl) if fn_decl.name == "foo"));
+    }
+
+    #[test]
+    fn recovers_at_next_declaration() {
+        let (decls, errors) = parse_with_errors("var a: int = 1 fn foo(let x int) { print x; } let b := 2;");
+
+        assert_eq!(errors, vec!["expected ';' at the end of declaration, found 'fn'", "expected ')' after parameters, found 'int'"]);
+        assert_eq!(decls.len(), 3);
+        assert!(matches!(decls[0], Decl::Error));
+        assert!(matches!(decls[1], Decl::Error));
+        assert!(matches!(&decls[2], Decl::Let(LetDecl { target: BindingTarget::Name(name), .. }) if name == "b"));
+    }
+
+    #[test]
+    fn recovers_at_next_statement() {
+        let (decls, errors) = parse_with_errors("fn foo() { let x := 1 +; print (2; if x { return; } } fn bar() {}");
+
+        assert_eq!(errors, vec!["expected expression, found ';'", "expected ')' after expression, found ';'"]);
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        assert_eq!(foo.stmts.len(), 3);
+        assert!(matches!(foo.stmts[0], Stmt::Error));
+        assert!(matches!(foo.stmts[1], Stmt::Error));
+        assert!(matches!(foo.stmts[2], Stmt::If(_)));
+        assert!(matches!(&decls[1], Decl::Fn(fn_decl) if fn_decl.name == "bar"));
+    }
+
+    #[test]
+    fn unclosed_block_stops_at_next_fn() {
+        let (decls, errors) = parse_with_errors("fn foo() { print 1; fn bar() {}");
+
+        assert_eq!(errors, vec!["expected '}' at the end of block, found 'fn'"]);
+        assert!(matches!(decls[0], Decl::Error));
+        assert!(matches!(&decls[1], Decl::Fn(fn_decl) if fn_decl.name == "bar"));
+    }
+
+    #[test]
+    fn rejects_integer_suffixes() {
+        let (_, errors) = parse_with_errors("let a := 1U; let b := 2.5D;");
+
+        assert_eq!(errors, vec!["integer suffixes aren't supported, 'int' is the only integer type"]);
+    }
+
+    #[test]
+    fn diagnostics_have_spans() {
+        let tokens = Lexer::new("/test.mv", "fn foo() {\n    print 1\n}").tokenize();
+        let mut stream = TokenStream::new(tokens);
+        DeclParser::new(&mut stream).parse();
+
+        let diagnostics = stream.take_diagnostics();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].span.line, diagnostics[0].span.column), (3, 1));
+    }
+
+    #[test]
+    fn parse_for_loops() {
+        let decls = parse("fn foo(let xs: [int]) { for i in 0..10 {} for var i in 0..=10 step 2 {} for x in xs {} }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        let Stmt::For(exclusive) = &foo.stmts[0] else { panic!("expected for loop") };
+        assert!(!exclusive.is_mutable);
+        assert!(matches!(&exclusive.iter, ForIter::Range { range, step: None } if !range.inclusive));
+
+        let Stmt::For(inclusive) = &foo.stmts[1] else { panic!("expected for loop") };
+        assert!(inclusive.is_mutable);
+        assert!(matches!(&inclusive.iter, ForIter::Range { range, step: Some(_) } if range.inclusive));
+
+        let Stmt::For(collection) = &foo.stmts[2] else { panic!("expected for loop") };
+        assert_eq!(collection.name, "x");
+        assert!(matches!(&collection.iter, ForIter::Collection(_)));
+    }
+
+    #[test]
+    fn parse_switch() {
+        let decls = parse("fn foo(let x: int) { switch x { 1, 2 -> print 1; 3..9 -> { print 2; fall; } else -> print 3; } }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+        let Stmt::Switch(switch_stmt) = &foo.stmts[0] else { panic!("expected switch") };
+
+        assert_eq!(switch_stmt.arms.len(), 3);
+        assert_eq!(switch_stmt.arms[0].cases.len(), 2);
+        assert!(matches!(&switch_stmt.arms[1].cases[..], [Pattern { kind: PatternKind::Range(range), .. }] if !range.inclusive));
+        assert!(matches!(switch_stmt.arms[1].body.stmts.last(), Some(Stmt::Fall(_))));
+        assert!(switch_stmt.arms[2].is_else);
+        assert!(switch_stmt.arms[2].cases.is_empty());
+    }
+
+    #[test]
+    fn recovers_after_error_in_switch_arm() {
+        let (decls, errors) = parse_with_errors("fn foo() { switch 3 { 1 -> print 1; fall; } print 2; } fn bar() {}");
+
+        assert_eq!(errors, vec!["expected expression, found 'fall'"]);
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        assert_eq!(foo.stmts.len(), 2);
+        assert!(matches!(foo.stmts[0], Stmt::Error));
+        assert!(matches!(foo.stmts[1], Stmt::Print(_)));
+        assert!(matches!(&decls[1], Decl::Fn(fn_decl) if fn_decl.name == "bar"));
+    }
+
+    #[test]
+    fn recovers_inside_nested_bodies() {
+        let source = "fn foo(let x: int) { if x > 0 { print (1; } loop { let y := ; break; } switch x { 1 -> { print *; fall; } else -> print 2; } print 3; }";
+        let (decls, errors) = parse_with_errors(source);
+
+        assert_eq!(errors.len(), 3, "{errors:?}");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        assert_eq!(foo.stmts.len(), 4);
+
+        let Stmt::If(if_stmt) = &foo.stmts[0] else { panic!("expected if") };
+        assert!(matches!(if_stmt.then_block.stmts[..], [Stmt::Error]));
+
+        let Stmt::Loop(loop_stmt) = &foo.stmts[1] else { panic!("expected loop") };
+        assert!(matches!(loop_stmt.body.stmts[..], [Stmt::Error, Stmt::Break(_)]));
+
+        let Stmt::Switch(switch_stmt) = &foo.stmts[2] else { panic!("expected switch") };
+        assert_eq!(switch_stmt.arms.len(), 2);
+        assert!(matches!(switch_stmt.arms[0].body.stmts[..], [Stmt::Error, Stmt::Fall(_)]));
+        assert!(matches!(foo.stmts[3], Stmt::Print(_)));
+    }
+
+    #[test]
+    fn parse_struct() {
+        let decls = parse("internal struct Point { x: int = 0, internal y: int, } fn foo() { let p := Point { x: 1, y: 2 }; if p.x == 1 { print p.y; } }");
+
+        let Decl::Struct(point) = &decls[0] else { panic!("expected struct declaration") };
+
+        assert!(point.is_internal);
+        assert_eq!(point.fields.len(), 2);
+        assert!(point.fields[0].default.is_some() && !point.fields[0].is_internal);
+        assert!(point.fields[1].default.is_none() && point.fields[1].is_internal);
+
+        let Decl::Fn(foo) = &decls[1] else { panic!("expected fn declaration") };
+
+        assert!(matches!(&foo.stmts[0], Stmt::Let(LetDecl { expr: Some(Expr { kind: ExprKind::StructLit(name, fields), .. }), .. }) if name.to_string() == "Point" && fields.len() == 2));
+        assert!(matches!(
+            &foo.stmts[1],
+            Stmt::If(IfStmt {
+                cond: Expr { kind: ExprKind::Binary(..), .. },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_enum() {
+        let decls = parse("enum Color { Red = 1, Green, } internal enum Msg { Move { x: int, y: int }, Write(string), Quit }");
+
+        let Decl::Enum(color) = &decls[0] else { panic!("expected enum declaration") };
+
+        assert!(!color.is_internal);
+        assert_eq!(color.variants.len(), 2);
+        assert!(matches!(
+            &color.variants[0],
+            EnumVariant {
+                payload: VariantPayload::Unit,
+                discriminant: Some(_),
+                ..
+            }
+        ));
+        assert!(matches!(
+            &color.variants[1],
+            EnumVariant {
+                payload: VariantPayload::Unit,
+                discriminant: None,
+                ..
+            }
+        ));
+
+        let Decl::Enum(msg) = &decls[1] else { panic!("expected enum declaration") };
+
+        assert!(msg.is_internal);
+        assert!(matches!(&msg.variants[0].payload, VariantPayload::Struct(fields) if fields.len() == 2));
+        assert!(matches!(&msg.variants[1].payload, VariantPayload::Tuple(types) if types.len() == 1));
+        assert!(matches!(&msg.variants[2].payload, VariantPayload::Unit));
+    }
+
+    #[test]
+    fn parse_paths() {
+        let decls = parse("fn foo() { let a := Color::Red; let b := Opt::Some(1); let c := Msg::Move { x: 1, y: 2 }; }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        assert!(matches!(&foo.stmts[0], Stmt::Let(LetDecl { expr: Some(Expr { kind: ExprKind::Path(path), .. }), .. }) if path.to_string() == "Color::Red"));
+        assert!(matches!(&foo.stmts[1], Stmt::Let(LetDecl { expr: Some(Expr { kind: ExprKind::Call(callee, args), .. }), .. }) if matches!(&callee.kind, ExprKind::Path(_)) && args.len() == 1));
+        assert!(matches!(&foo.stmts[2], Stmt::Let(LetDecl { expr: Some(Expr { kind: ExprKind::StructLit(path, _), .. }), .. }) if path.segments == ["Msg", "Move"]));
+    }
+
+    #[test]
+    fn parse_impl() {
+        let decls = parse("impl Point { init(let x: int) {} fn len(self) -> float { return self.x; } fn origin() -> Point { return Point::new(); } } fn foo() { p.len(); }");
+
+        let Decl::Impl(impl_decl) = &decls[0] else { panic!("expected impl declaration") };
+
+        assert_eq!(impl_decl.type_name, "Point");
+        assert_eq!(impl_decl.fns.len(), 3);
+        assert_eq!(impl_decl.fns[0].name, "init");
+        assert!(impl_decl.fns[1].params[0].is_self());
+        assert!(impl_decl.fns[2].params.is_empty());
+
+        let Decl::Fn(foo) = &decls[1] else { panic!("expected fn declaration") };
+
+        assert!(matches!(&foo.stmts[0], Stmt::Expr(Expr { kind: ExprKind::Call(callee, _), .. }) if matches!(&callee.kind, ExprKind::Field(_, name) if name == "len")));
+    }
+
+    #[test]
+    fn parse_defer() {
+        let decls = parse("fn foo() { defer print 1; defer { print 2; } }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        assert!(matches!(&foo.stmts[0], Stmt::Defer(defer_stmt) if matches!(*defer_stmt.stmt, Stmt::Print(_))));
+        assert!(matches!(&foo.stmts[1], Stmt::Defer(defer_stmt) if matches!(*defer_stmt.stmt, Stmt::Block(_))));
+    }
+
+    #[test]
+    fn parse_imports() {
+        let decls = parse("import \"lib/util\"; import math::vec; fn foo(let v: vec::Vec3) -> float { return vec::len(v); }");
+
+        assert!(matches!(&decls[0], Decl::Import(ImportDecl { path: ImportPath::File(path), .. }) if path == "lib/util"));
+        assert!(matches!(&decls[1], Decl::Import(ImportDecl { path: ImportPath::Module(segments), .. }) if segments == &["math", "vec"]));
+
+        let Decl::Fn(foo) = &decls[2] else { panic!("expected fn declaration") };
+
+        assert!(matches!(&foo.params[0].type_info, Some(TypeInfo { kind: TypeKind::Path(path), .. }) if path.to_string() == "vec::Vec3"));
+    }
+
+    #[test]
+    fn parse_internal_items() {
+        let decls = parse("internal var a: int = 1; internal let b := 2; internal fn f() {} fn g() {} impl P { internal fn h(self) {} fn i(self) {} }");
+
+        assert!(matches!(&decls[0], Decl::Var(VarDecl { is_internal: true, .. })));
+        assert!(matches!(&decls[1], Decl::Let(LetDecl { is_internal: true, .. })));
+        assert!(matches!(&decls[2], Decl::Fn(FnDecl { is_internal: true, .. })));
+        assert!(matches!(&decls[3], Decl::Fn(FnDecl { is_internal: false, .. })));
+
+        let Decl::Impl(impl_decl) = &decls[4] else { panic!("expected impl declaration") };
+
+        assert!(impl_decl.fns[0].is_internal);
+        assert!(!impl_decl.fns[1].is_internal);
+    }
+
+    #[test]
+    fn parse_closures() {
+        let decls = parse("fn foo(let f: fn(int, int) -> int, let g: fn()) { let a := |let x, var acc| x + acc; let b := || { print 1; }; let c := |let x: int| -> int { return x; }; }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        assert!(matches!(&foo.params[0].type_info, Some(TypeInfo { kind: TypeKind::Fn(params, Some(_)), .. }) if params.len() == 2));
+        assert!(matches!(&foo.params[1].type_info, Some(TypeInfo { kind: TypeKind::Fn(params, None), .. }) if params.is_empty()));
+
+        let closures = foo
+            .stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::Let(LetDecl {
+                    expr: Some(Expr { kind: ExprKind::Closure(closure), .. }),
+                    ..
+                }) => closure,
+                _ => panic!("expected closure"),
+            })
+            .collect::<Vec<_>>();
+
+        assert!(closures[0].params[1].is_mutable && matches!(closures[0].body, ClosureBody::Expr(_)));
+        assert!(closures[1].params.is_empty() && matches!(closures[1].body, ClosureBody::Block(_)));
+        assert!(closures[2].return_type.is_some() && matches!(closures[2].body, ClosureBody::Block(_)));
+    }
+
+    #[test]
+    fn parse_generics() {
+        let decls = parse("fn max<T>(let a: T, let b: T) -> T { return a; } struct Pair<A, B> { first: A, second: B } impl Pair<A, B> { fn get(self) -> Pair<A, B> { return self; } } fn main() { let m := max::<int>(1, 2); }");
+
+        let Decl::Fn(max) = &decls[0] else { panic!("expected fn declaration") };
+        let Decl::Struct(pair) = &decls[1] else { panic!("expected struct declaration") };
+        let Decl::Impl(impl_decl) = &decls[2] else { panic!("expected impl declaration") };
+        let Decl::Fn(main) = &decls[3] else { panic!("expected fn declaration") };
+
+        assert_eq!(max.type_params[0].name, "T");
+        assert_eq!(pair.type_params.iter().map(|type_param| type_param.name.as_str()).collect::<Vec<_>>(), vec!["A", "B"]);
+        assert_eq!(impl_decl.type_params.len(), 2);
+        assert!(matches!(&impl_decl.fns[0].return_type, Some(TypeInfo { kind: TypeKind::Generic(_, args), .. }) if args.len() == 2));
+        assert!(
+            matches!(&main.stmts[0], Stmt::Let(LetDecl { expr: Some(Expr { kind: ExprKind::Call(callee, _), .. }), .. }) if matches!(&callee.kind, ExprKind::Generic(path, args) if path.segments == ["max"] && args.len() == 1))
+        );
+    }
+
+    #[test]
+    fn parse_attributes() {
+        let (decls, errors) = parse_with_errors(
+            "#[test] fn foo() { #[inline] print 1; } #[deprecated(\"use Bar\")] internal struct Foo { #[deprecated(\"gone\")] x: int } \
+             impl Foo { #[inline] fn get(self) -> int { return self.x; } } #[inline] let a := 1;",
+        );
+
+        assert_eq!(errors, vec!["attributes are only allowed on functions, structs, fields and statements"]);
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+        let Decl::Struct(struct_decl) = &decls[1] else { panic!("expected struct declaration") };
+        let Decl::Impl(impl_decl) = &decls[2] else { panic!("expected impl declaration") };
+
+        assert_eq!(foo.attrs[0].name, "test");
+        assert!(matches!(&foo.stmts[0], Stmt::Attributed(attributed) if attributed.attrs[0].name == "inline" && matches!(*attributed.stmt, Stmt::Print(_))));
+        assert!(struct_decl.is_internal);
+        assert!(matches!(&struct_decl.attrs[0].args[..], [Expr { kind: ExprKind::Str(message), .. }] if message == "use Bar"));
+        assert_eq!(struct_decl.fields[0].attrs[0].name, "deprecated");
+        assert_eq!(impl_decl.fns[0].attrs[0].name, "inline");
+        assert!(matches!(decls[3], Decl::Let(_)));
+    }
+
+    #[test]
+    fn parse_pointers() {
+        let decls = parse("fn foo(let p: ^^int) { let q: ^int = nil; let x := @p^.x; let y := -p^^; }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        assert!(matches!(&foo.params[0].type_info, Some(TypeInfo { kind: TypeKind::Pointer(pointee), .. }) if matches!(pointee.kind, TypeKind::Pointer(_))));
+        assert!(matches!(
+            &foo.stmts[0],
+            Stmt::Let(LetDecl {
+                expr: Some(Expr { kind: ExprKind::Nil, .. }),
+                ..
+            })
+        ));
+
+        let Stmt::Let(LetDecl { expr: Some(addr), .. }) = &foo.stmts[1] else { panic!("expected let") };
+        let ExprKind::Unary(UnaryOp::AddrOf, field) = &addr.kind else { panic!("expected address-of") };
+        assert!(matches!(&field.kind, ExprKind::Field(base, _) if matches!(base.kind, ExprKind::Unary(UnaryOp::Deref, _))));
+
+        let Stmt::Let(LetDecl { expr: Some(neg), .. }) = &foo.stmts[2] else { panic!("expected let") };
+        assert!(matches!(&neg.kind, ExprKind::Unary(UnaryOp::Neg, deref) if matches!(&deref.kind, ExprKind::Unary(UnaryOp::Deref, inner) if matches!(inner.kind, ExprKind::Unary(UnaryOp::Deref, _)))));
+    }
+
+    #[test]
+    fn parse_arrays() {
+        let decls = parse("fn foo() { let a := [1, 2, 3]; let b := [0; 16]; let c := a[1]; let d := a[1..n]; let e := b[0..=2][1]; let f := []; }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        let exprs = foo
+            .stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::Let(LetDecl { expr: Some(expr), .. }) => &expr.kind,
+                _ => panic!("expected let"),
+            })
+            .collect::<Vec<_>>();
+
+        assert!(matches!(exprs[0], ExprKind::Array(elems) if elems.len() == 3));
+        assert!(matches!(exprs[1], ExprKind::ArrayRepeat(_, count) if matches!(count.kind, ExprKind::Int(16))));
+        assert!(matches!(exprs[2], ExprKind::Index(_, _)));
+        assert!(matches!(exprs[3], ExprKind::Slice(_, range) if !range.inclusive));
+        assert!(matches!(exprs[4], ExprKind::Index(base, _) if matches!(&base.kind, ExprKind::Slice(_, range) if range.inclusive)));
+        assert!(matches!(exprs[5], ExprKind::Array(elems) if elems.is_empty()));
+    }
+
+    #[test]
+    fn parse_increments() {
+        let decls = parse("fn foo() { let a := ++x; let b := x--; let c := -p^++; let d := a[0]++ + --b.y; }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        let exprs = foo
+            .stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::Let(LetDecl { expr: Some(expr), .. }) => &expr.kind,
+                _ => panic!("expected let"),
+            })
+            .collect::<Vec<_>>();
+
+        assert!(matches!(exprs[0], ExprKind::Update(UpdateOp::PreInc, operand) if matches!(operand.kind, ExprKind::Ident(_))));
+        assert!(matches!(exprs[1], ExprKind::Update(UpdateOp::PostDec, operand) if matches!(operand.kind, ExprKind::Ident(_))));
+        assert!(matches!(exprs[2], ExprKind::Unary(UnaryOp::Neg, inc) if matches!(&inc.kind, ExprKind::Update(UpdateOp::PostInc, deref) if matches!(deref.kind, ExprKind::Unary(UnaryOp::Deref, _)))));
+        assert!(matches!(exprs[3], ExprKind::Binary(_, left, right)
+            if matches!(&left.kind, ExprKind::Update(UpdateOp::PostInc, index) if matches!(index.kind, ExprKind::Index(_, _)))
+                && matches!(&right.kind, ExprKind::Update(UpdateOp::PreDec, field) if matches!(field.kind, ExprKind::Field(_, _)))));
+    }
+
+    #[test]
+    fn parse_assignments() {
+        let decls = parse("fn foo() { x = 1; a = b = c + 1; p^.x += 2; xs[i] *= y ? 2 : 3; let z := n /= 2; }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        let exprs = foo
+            .stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::Expr(expr) | Stmt::Let(LetDecl { expr: Some(expr), .. }) => &expr.kind,
+                _ => panic!("expected expression"),
+            })
+            .collect::<Vec<_>>();
+
+        assert!(matches!(exprs[0], ExprKind::Assign(None, target, _) if matches!(target.kind, ExprKind::Ident(_))));
+        assert!(matches!(exprs[1], ExprKind::Assign(None, _, value)
+            if matches!(&value.kind, ExprKind::Assign(None, _, sum) if matches!(sum.kind, ExprKind::Binary(BinaryOp::Add, _, _)))));
+        assert!(matches!(exprs[2], ExprKind::Assign(Some(BinaryOp::Add), target, _) if matches!(target.kind, ExprKind::Field(_, _))));
+        assert!(matches!(exprs[3], ExprKind::Assign(Some(BinaryOp::Mul), target, value)
+            if matches!(target.kind, ExprKind::Index(_, _)) && matches!(value.kind, ExprKind::Ternary(_, _, _))));
+        assert!(matches!(exprs[4], ExprKind::Assign(Some(BinaryOp::Div), _, _)));
+    }
+
+    #[test]
+    fn parse_optionals() {
+        let decls = parse("fn foo(let p: ?^Point) -> ?int { let a := p?.x ?? 0; let b := p?.next?.len(); if let q = p { return q.x; } return nil; }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+
+        assert!(matches!(&foo.params[0].type_info, Some(TypeInfo { kind: TypeKind::Optional(inner), .. }) if matches!(inner.kind, TypeKind::Pointer(_))));
+        assert!(matches!(&foo.return_type, Some(TypeInfo { kind: TypeKind::Optional(_), .. })));
+
+        let Stmt::Let(LetDecl { expr: Some(a), .. }) = &foo.stmts[0] else { panic!("expected let") };
+        assert!(matches!(&a.kind, ExprKind::Coalesce(field, default) if matches!(field.kind, ExprKind::OptionalField(_, _)) && matches!(default.kind, ExprKind::Int(0))));
+
+        let Stmt::Let(LetDecl { expr: Some(b), .. }) = &foo.stmts[1] else { panic!("expected let") };
+        let ExprKind::Call(callee, _) = &b.kind else { panic!("expected call") };
+        assert!(matches!(&callee.kind, ExprKind::OptionalField(base, method) if method == "len" && matches!(base.kind, ExprKind::OptionalField(_, _))));
+
+        let Stmt::If(if_let) = &foo.stmts[2] else { panic!("expected if") };
+        assert_eq!(if_let.binding.as_deref(), Some("q"));
+        assert!(matches!(if_let.cond.kind, ExprKind::Ident(_)));
+    }
+
+    #[test]
+    fn parse_consts() {
+        let decls = parse("const SIZE: int = 4 * 16; internal const fn square(let x: int) -> int { return x * x; } internal const NAME: string = \"mova\";");
+
+        assert!(matches!(&decls[0], Decl::Const(ConstDecl { name, is_internal: false, expr, .. }) if name == "SIZE" && matches!(expr.kind, ExprKind::Binary(BinaryOp::Mul, _, _))));
+        assert!(matches!(
+            &decls[1],
+            Decl::Fn(FnDecl {
+                is_const: true,
+                is_internal: true,
+                ..
+            })
+        ));
+        assert!(matches!(&decls[2], Decl::Const(ConstDecl { is_internal: true, type_info: TypeInfo { kind: TypeKind::Base(ty), .. }, .. }) if ty == "string"));
+    }
+
+    #[test]
+    fn parse_patterns() {
+        let decls = parse("fn foo() { switch s { Shape::Circle(r) if r > 1 -> print r; Point { x: 0, y, .. } -> print y; Shape::Empty | 1..=3 -> print 0; n -> print n; _ -> print 1; } }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+        let Stmt::Switch(switch_stmt) = &foo.stmts[0] else { panic!("expected switch") };
+
+        let arm = &switch_stmt.arms[0];
+        assert!(matches!(&arm.cases[..], [Pattern { kind: PatternKind::Variant(path, Some(args)), .. }]
+            if path.to_string() == "Shape::Circle" && matches!(&args[..], [Pattern { kind: PatternKind::Ident(r), .. }] if r == "r")));
+        assert!(matches!(
+            &arm.guard,
+            Some(Expr {
+                kind: ExprKind::Binary(BinaryOp::Greater, _, _),
+                ..
+            })
+        ));
+
+        let arm = &switch_stmt.arms[1];
+        let [Pattern {
+            kind: PatternKind::Struct(path, fields, true),
+            ..
+        }] = &arm.cases[..]
+        else {
+            panic!("expected struct pattern")
+        };
+        assert_eq!(path.to_string(), "Point");
+        assert!(matches!(&fields[0].pattern.kind, PatternKind::Expr(Expr { kind: ExprKind::Int(0), .. })));
+        assert!(matches!(&fields[1].pattern.kind, PatternKind::Ident(y) if y == "y"));
+
+        assert!(matches!(&switch_stmt.arms[2].cases[..], [Pattern { kind: PatternKind::Variant(_, None), .. }, Pattern { kind: PatternKind::Range(range), .. }] if range.inclusive));
+        assert!(matches!(&switch_stmt.arms[3].cases[..], [Pattern { kind: PatternKind::Ident(_), .. }]));
+        assert!(matches!(&switch_stmt.arms[4].cases[..], [Pattern { kind: PatternKind::Wildcard, .. }]));
+    }
+
+    #[test]
+    fn parse_tuples() {
+        let decls = parse("fn divmod(let a: int, let b: int) -> (int, int) { return (a / b, a - b,); } fn foo() { let (q, _) := divmod(7, 2); print t.0.1 + (q); }");
+
+        let Decl::Fn(divmod) = &decls[0] else { panic!("expected fn declaration") };
+        assert!(matches!(&divmod.return_type, Some(TypeInfo { kind: TypeKind::Tuple(elems), .. }) if elems.len() == 2));
+        assert!(matches!(&divmod.stmts[0], Stmt::Return(ReturnStmt { expr: Some(Expr { kind: ExprKind::Tuple(elems), .. }), .. }) if elems.len() == 2));
+
+        let Decl::Fn(foo) = &decls[1] else { panic!("expected fn declaration") };
+        assert!(matches!(&foo.stmts[0], Stmt::Let(LetDecl { target: BindingTarget::Tuple(names), type_info: None, .. }) if names == &["q", "_"]));
+
+        let Stmt::Print(Expr {
+            kind: ExprKind::Binary(BinaryOp::Add, left, right),
+            ..
+        }) = &foo.stmts[1]
+        else {
+            panic!("expected print")
+        };
+        assert!(matches!(&left.kind, ExprKind::Field(base, index) if index == "1" && matches!(&base.kind, ExprKind::Field(_, index) if index == "0")));
+        assert!(matches!(right.kind, ExprKind::Ident(_)));
+    }
+
+    #[test]
+    fn parse_labeled_loops() {
+        let decls = parse("fn foo() { outer: for i in 0..3 { rows: while true { continue outer; break rows; } } let x := loop { break 2 + 1; }; break; }");
+
+        let Decl::Fn(foo) = &decls[0] else { panic!("expected fn declaration") };
+        let Stmt::For(outer) = &foo.stmts[0] else { panic!("expected for") };
+        assert_eq!(outer.label.as_deref(), Some("outer"));
+
+        let Stmt::While(rows) = &outer.body.stmts[0] else { panic!("expected while") };
+        assert_eq!(rows.label.as_deref(), Some("rows"));
+        assert!(matches!(&rows.body.stmts[0], Stmt::Continue(ContinueStmt { label: Some(label), .. }) if label == "outer"));
+        assert!(matches!(&rows.body.stmts[1], Stmt::Break(BreakStmt { target: BreakTarget::Name(name, _), .. }) if name == "rows"));
+
+        let Stmt::Let(LetDecl {
+            expr: Some(Expr { kind: ExprKind::Loop(loop_stmt), .. }),
+            ..
+        }) = &foo.stmts[1]
+        else {
+            panic!("expected loop expression")
+        };
+        assert!(matches!(
+            &loop_stmt.body.stmts[0],
+            Stmt::Break(BreakStmt {
+                target: BreakTarget::Value(Expr { kind: ExprKind::Binary(..), .. }),
+                ..
+            })
+        ));
+        assert!(matches!(&foo.stmts[2], Stmt::Break(BreakStmt { target: BreakTarget::None, .. })));
+    }
+
+    #[test]
+    fn parse_named_args() {
+        let decls = parse("fn connect(let host: string, let port: int = 80, var retries: int = 3) {} fn main() { connect(\"x\", port: 8080, retries: cond ? 1 : 2); }");
+
+        let Decl::Fn(connect) = &decls[0] else { panic!("expected fn declaration") };
+        assert!(connect.params[0].default.is_none());
+        assert!(matches!(&connect.params[1].default, Some(Expr { kind: ExprKind::Int(80), .. })));
+        assert!(connect.params[2].is_mutable && connect.params[2].default.is_some());
+
+        let Decl::Fn(main) = &decls[1] else { panic!("expected fn declaration") };
+        let Stmt::Expr(Expr { kind: ExprKind::Call(_, args), .. }) = &main.stmts[0] else {
+            panic!("expected call")
+        };
+        assert!(args[0].name.is_none() && matches!(args[0].value.kind, ExprKind::Str(_)));
+        assert!(matches!(&args[1], Arg { name: Some(name), value: Expr { kind: ExprKind::Int(8080), .. }, .. } if name == "port"));
+        assert!(matches!(&args[2], Arg { name: Some(name), value: Expr { kind: ExprKind::Ternary(..), .. }, .. } if name == "retries"));
+    }
+
+    #[test]
+    fn parse_variadics() {
+        let decls = parse("fn log(let fmt: string, let args: ...any) {} fn main() { log(\"x\", 1, ...rest[1..n]); }");
+
+        let Decl::Fn(log) = &decls[0] else { panic!("expected fn declaration") };
+        assert!(!log.params[0].is_variadic);
+        assert!(log.params[1].is_variadic);
+        assert!(matches!(&log.params[1].type_info, Some(TypeInfo { kind: TypeKind::Base(name), .. }) if name == "any"));
+
+        let Decl::Fn(main) = &decls[1] else { panic!("expected fn declaration") };
+        let Stmt::Expr(Expr { kind: ExprKind::Call(_, args), .. }) = &main.stmts[0] else {
+            panic!("expected call")
+        };
+        assert!(!args[0].spread && !args[1].spread);
+        assert!(matches!(
+            &args[2],
+            Arg {
+                name: None,
+                spread: true,
+                value: Expr { kind: ExprKind::Slice(..), .. },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_interfaces() {
+        let decls = parse(
+            "interface Show { fn show(self) -> string; fn pad(self, let width: int); } internal interface Hidden {} \
+             impl Show for Point { fn show(self) -> string { return \"p\"; } } impl fmt::Debug for Pair<A, B> {} \
+             fn print_all<T: Show + fmt::Debug, U>(let items: ...T) {}",
+        );
+
+        let Decl::Interface(show) = &decls[0] else { panic!("expected interface declaration") };
+        assert!(show.name == "Show" && !show.is_internal);
+        assert_eq!(show.methods.iter().map(|method| method.name.as_str()).collect::<Vec<_>>(), ["show", "pad"]);
+        assert!(show.methods.iter().all(|method| method.stmts.is_empty()));
+        assert!(show.methods[0].params[0].is_self() && show.methods[0].return_type.is_some());
+        assert!(matches!(&decls[1], Decl::Interface(hidden) if hidden.is_internal && hidden.methods.is_empty()));
+
+        let Decl::Impl(point) = &decls[2] else { panic!("expected impl declaration") };
+        assert_eq!(point.interface.as_ref().map(|path| path.to_string()), Some("Show".to_string()));
+        assert_eq!(point.type_name, "Point");
+        assert_eq!(point.fns.len(), 1);
+
+        let Decl::Impl(pair) = &decls[3] else { panic!("expected impl declaration") };
+        assert_eq!(pair.interface.as_ref().map(|path| path.to_string()), Some("fmt::Debug".to_string()));
+        assert_eq!(pair.type_params.len(), 2);
+
+        let Decl::Fn(print_all) = &decls[4] else { panic!("expected fn declaration") };
+        let bounds = print_all.type_params[0].bounds.iter().map(|path| path.to_string()).collect::<Vec<_>>();
+        assert_eq!(bounds, ["Show", "fmt::Debug"]);
+        assert!(print_all.type_params[1].bounds.is_empty());
     }
 }
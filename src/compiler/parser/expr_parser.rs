@@ -1,52 +1,467 @@
-use crate::compiler::ast::expr::Expr;
+use crate::compiler::ast::expr::{Arg, BinaryOp, Closure, ClosureBody, Expr, ExprKind, FieldInit, Path, Range, UnaryOp, UpdateOp};
+use crate::compiler::diagnostic::Span;
+use crate::compiler::token::{TokenKind, TokenSuffix, Value};
 use crate::compiler::token_stream::TokenStream;
 
+use super::decl_parser::DeclParser;
+use super::stmt_parser::StmtParser;
+use super::type_parser::TypeParser;
+use super::ParseResult;
+
 pub struct ExprParser<'a> {
     stream: &'a mut TokenStream,
+    no_struct_literal: bool,
 }
 
 impl<'a> ExprParser<'a> {
     pub fn new(stream: &'a mut TokenStream) -> Self {
-        ExprParser { stream: stream }
+        ExprParser { stream, no_struct_literal: false }
+    }
+
+    /// Disallows struct literals outside of parentheses, for expressions followed by a block such as `if` conditions,
+    /// where `x {` starts the block rather than a literal.
+    pub fn no_struct_literal(mut self) -> Self {
+        self.no_struct_literal = true;
+        self
+    }
+
+    pub fn parse(&mut self) -> ParseResult<Expr> {
+        self.parse_assign()
+    }
+
+    /// Parses the `..end` or `..=end` part of a range whose start has already been parsed.
+    pub fn parse_range(&mut self, start: Expr) -> ParseResult<Range> {
+        let inclusive = match self.stream.current().kind {
+            TokenKind::DotDot => false,
+            TokenKind::DotDotEqual => true,
+            _ => return Err(self.stream.error_at_current("expected '..' or '..='")),
+        };
+
+        self.stream.next();
+        let end = self.parse()?;
+
+        Ok(Range {
+            start: Box::new(start),
+            end: Box::new(end),
+            inclusive,
+        })
+    }
+
+    /// Assignments are right-associative, `a = b = 0` assigns to `b` first.
+    fn parse_assign(&mut self) -> ParseResult<Expr> {
+        let target = self.parse_ternary()?;
+
+        if !self.stream.is_assign() {
+            return Ok(target);
+        }
+
+        let token = self.stream.next().unwrap();
+        let span = token.span();
+
+        let op = match token.kind {
+            TokenKind::Equal => None,
+            TokenKind::PlusEqual => Some(BinaryOp::Add),
+            TokenKind::MinusEqual => Some(BinaryOp::Sub),
+            TokenKind::StarEqual => Some(BinaryOp::Mul),
+            TokenKind::SlashEqual => Some(BinaryOp::Div),
+            kind => unreachable!("{kind:?} is not an assignment operator"),
+        };
+
+        let value = self.parse_assign()?;
+
+        Ok(Expr::new(ExprKind::Assign(op, Box::new(target), Box::new(value)), span))
+    }
+
+    fn parse_ternary(&mut self) -> ParseResult<Expr> {
+        let cond = self.parse_coalesce()?;
+
+        if !self.stream.check_current(TokenKind::Question) {
+            return Ok(cond);
+        }
+
+        let span = self.stream.next().unwrap().span();
+        let then_expr = self.parse_ternary()?;
+        self.stream.next_expected(TokenKind::Colon, "expected ':' in ternary expression")?;
+        let else_expr = self.parse_ternary()?;
+
+        Ok(Expr::new(ExprKind::Ternary(Box::new(cond), Box::new(then_expr), Box::new(else_expr)), span))
+    }
+
+    fn parse_coalesce(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_or()?;
+
+        while self.stream.advance_if_match(TokenKind::QuestionQuestion) {
+            let span = self.stream.previous().span();
+            let right = self.parse_or()?;
+            expr = Expr::new(ExprKind::Coalesce(Box::new(expr), Box::new(right)), span);
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_or(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_and()?;
+
+        while self.stream.advance_if_match(TokenKind::Or) {
+            let span = self.stream.previous().span();
+            let right = self.parse_and()?;
+            expr = Expr::new(ExprKind::Binary(BinaryOp::Or, Box::new(expr), Box::new(right)), span);
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_cmp()?;
+
+        while self.stream.advance_if_match(TokenKind::And) {
+            let span = self.stream.previous().span();
+            let right = self.parse_cmp()?;
+            expr = Expr::new(ExprKind::Binary(BinaryOp::And, Box::new(expr), Box::new(right)), span);
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_cmp(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_temp()?;
+
+        while self.stream.advance_if_cmp() {
+            let (op, span) = self.previous_binary_op();
+            let right = self.parse_temp()?;
+            expr = Expr::new(ExprKind::Binary(op, Box::new(expr), Box::new(right)), span);
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_temp(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_factor()?;
+
+        while self.stream.advance_if_match_any(&[TokenKind::Plus, TokenKind::Minus]) {
+            let (op, span) = self.previous_binary_op();
+            let right = self.parse_factor()?;
+            expr = Expr::new(ExprKind::Binary(op, Box::new(expr), Box::new(right)), span);
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_factor(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_unary()?;
+
+        while self.stream.advance_if_match_any(&[TokenKind::Star, TokenKind::Slash]) {
+            let (op, span) = self.previous_binary_op();
+            let right = self.parse_unary()?;
+            expr = Expr::new(ExprKind::Binary(op, Box::new(expr), Box::new(right)), span);
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> ParseResult<Expr> {
+        if !self.stream.advance_if_unary() {
+            return self.parse_base();
+        }
+
+        let token = self.stream.previous();
+        let (kind, span) = (token.kind, token.span());
+        let operand = Box::new(self.parse_unary()?);
+
+        let kind = match kind {
+            TokenKind::Minus => ExprKind::Unary(UnaryOp::Neg, operand),
+            TokenKind::Bang => ExprKind::Unary(UnaryOp::Not, operand),
+            TokenKind::At => ExprKind::Unary(UnaryOp::AddrOf, operand),
+            TokenKind::Inc => ExprKind::Update(UpdateOp::PreInc, operand),
+            TokenKind::Dec => ExprKind::Update(UpdateOp::PreDec, operand),
+            kind => unreachable!("{kind:?} is not a unary operator"),
+        };
+
+        Ok(Expr::new(kind, span))
+    }
+
+    fn parse_base(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_operand()?;
+
+        loop {
+            match self.stream.current().kind {
+                TokenKind::LeftParen => {
+                    let span = self.stream.next().unwrap().span();
+                    let mut args = Vec::<Arg>::new();
+
+                    while !self.stream.check_current(TokenKind::RightParen) {
+                        args.push(self.parse_arg()?);
+
+                        if !self.stream.advance_if_match(TokenKind::Comma) {
+                            break;
+                        }
+                    }
+
+                    self.stream.next_expected(TokenKind::RightParen, "expected ')' after arguments")?;
+                    expr = Expr::new(ExprKind::Call(Box::new(expr), args), span);
+                }
+                TokenKind::Dot => {
+                    let span = self.stream.next().unwrap().span();
+                    let field = self.parse_field_name("'.'")?;
+                    expr = Expr::new(ExprKind::Field(Box::new(expr), field), span);
+                }
+                TokenKind::QuestionDot => {
+                    let span = self.stream.next().unwrap().span();
+                    let field = self.parse_field_name("'?.'")?;
+                    expr = Expr::new(ExprKind::OptionalField(Box::new(expr), field), span);
+                }
+                TokenKind::LeftBracket => expr = self.parse_index(expr)?,
+                TokenKind::Inc | TokenKind::Dec => {
+                    let token = self.stream.next().unwrap();
+                    let op = if token.kind == TokenKind::Inc { UpdateOp::PostInc } else { UpdateOp::PostDec };
+                    let span = token.span();
+
+                    expr = Expr::new(ExprKind::Update(op, Box::new(expr)), span);
+                }
+                TokenKind::Hat => {
+                    let span = self.stream.next().unwrap().span();
+                    expr = Expr::new(ExprKind::Unary(UnaryOp::Deref, Box::new(expr)), span);
+                }
+                _ => return Ok(expr),
+            }
+        }
     }
 
-    pub fn parse(&mut self) -> Option<Expr> {
-        self.parse_ternary()
+    /// Parses `[index]` or `[start..end]` after the indexed expression.
+    fn parse_index(&mut self, base: Expr) -> ParseResult<Expr> {
+        let span = self.stream.next_expected(TokenKind::LeftBracket, "expected '['")?.span();
+        let index = self.parse_nested()?;
+
+        let kind = if matches!(self.stream.current().kind, TokenKind::DotDot | TokenKind::DotDotEqual) {
+            let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+            let range = self.parse_range(index);
+            self.no_struct_literal = no_struct_literal;
+
+            ExprKind::Slice(Box::new(base), range?)
+        } else {
+            ExprKind::Index(Box::new(base), Box::new(index))
+        };
+
+        self.stream.next_expected(TokenKind::RightBracket, "expected ']' after index")?;
+
+        Ok(Expr::new(kind, span))
     }
 
-    fn parse_ternary(&mut self) -> Option<Expr> {
-        None
+    /// Parses `[elem, ...]` or `[value; count]`.
+    fn parse_array(&mut self) -> ParseResult<Expr> {
+        let span = self.stream.next_expected(TokenKind::LeftBracket, "expected '['")?.span();
+        let mut elems = Vec::<Expr>::new();
+
+        while !self.stream.check_current(TokenKind::RightBracket) {
+            elems.push(self.parse_nested()?);
+
+            if elems.len() == 1 && self.stream.advance_if_match(TokenKind::Semicolon) {
+                let count = self.parse_nested()?;
+                self.stream.next_expected(TokenKind::RightBracket, "expected ']' after array length")?;
+
+                return Ok(Expr::new(ExprKind::ArrayRepeat(Box::new(elems.remove(0)), Box::new(count)), span));
+            }
+
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        self.stream.next_expected(TokenKind::RightBracket, "expected ']' at the end of array")?;
+
+        Ok(Expr::new(ExprKind::Array(elems), span))
     }
 
-    fn parse_or(&mut self) -> Option<Expr> {
-        None
+    /// Parses `Name { field: expr, ... }` after the struct name.
+    fn parse_struct_literal(&mut self, name: Path, span: Span) -> ParseResult<Expr> {
+        self.stream.next_expected(TokenKind::LeftBrace, "expected '{' after struct name")?;
+
+        let mut fields = Vec::<FieldInit>::new();
+
+        while !self.stream.check_current(TokenKind::RightBrace) {
+            let field = self.stream.next_expected(TokenKind::Identifier, "expected field name")?.clone();
+            self.stream.next_expected(TokenKind::Colon, "expected ':' after field name")?;
+
+            fields.push(FieldInit {
+                name: field.value.get_str(),
+                expr: self.parse_nested()?,
+                span: field.span(),
+            });
+
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        self.stream.next_expected(TokenKind::RightBrace, "expected '}' at the end of struct literal")?;
+
+        Ok(Expr::new(ExprKind::StructLit(name, fields), span))
     }
 
-    fn parse_and(&mut self) -> Option<Expr> {
-        None
+    /// Parses `|params| expr`, `|params| { ... }` or `|params| -> type { ... }`.
+    fn parse_closure(&mut self) -> ParseResult<Expr> {
+        let span = self.stream.next_expected(TokenKind::Pipe, "expected '|'")?.span();
+        let mut params = Vec::new();
+
+        while !self.stream.check_current(TokenKind::Pipe) {
+            params.push(DeclParser::new(self.stream).parse_fn_param()?);
+
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        self.stream.next_expected(TokenKind::Pipe, "expected '|' after closure parameters")?;
+
+        let return_type = if self.stream.advance_if_match(TokenKind::MinusGreater) {
+            Some(TypeParser::new(self.stream).parse()?)
+        } else {
+            None
+        };
+
+        let body = if return_type.is_some() || self.stream.check_current(TokenKind::LeftBrace) {
+            ClosureBody::Block(StmtParser::new(self.stream).parse_block()?)
+        } else {
+            ClosureBody::Expr(self.parse()?)
+        };
+
+        let closure = Closure { params, return_type, body };
+
+        Ok(Expr::new(ExprKind::Closure(Box::new(closure)), span))
     }
 
-    fn parse_cmp(&mut self) -> Option<Expr> {
-        None
+    /// Parses an expression enclosed in delimiters, where struct literals are unambiguous again.
+    fn parse_nested(&mut self) -> ParseResult<Expr> {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+        let expr = self.parse();
+        self.no_struct_literal = no_struct_literal;
+
+        expr
     }
 
-    fn parse_temp(&mut self) -> Option<Expr> {
-        None
+    fn parse_operand(&mut self) -> ParseResult<Expr> {
+        let token = self.stream.current().clone();
+        let span = token.span();
+
+        let kind = match (token.kind, token.value) {
+            (TokenKind::Int, Value::Int(value)) if token.suffix == TokenSuffix::None => ExprKind::Int(value),
+            (TokenKind::Int, _) => return Err(self.stream.error("integer suffixes aren't supported, 'int' is the only integer type", span)),
+            (TokenKind::Float, Value::Float(value)) => ExprKind::Float(value),
+            (TokenKind::String, Value::Str(value)) => ExprKind::Str(value),
+            (TokenKind::True, _) => ExprKind::Bool(true),
+            (TokenKind::False, _) => ExprKind::Bool(false),
+            (TokenKind::Nil, _) => ExprKind::Nil,
+            (TokenKind::Identifier, Value::Str(name)) => {
+                self.stream.next();
+
+                let mut path = Path { segments: vec![name] };
+
+                while self.stream.advance_if_match(TokenKind::ColonColon) {
+                    if self.stream.check_current(TokenKind::Less) {
+                        let type_args = TypeParser::new(self.stream).parse_args()?;
+                        return Ok(Expr::new(ExprKind::Generic(path, type_args), span));
+                    }
+
+                    path.segments.push(self.stream.next_expected(TokenKind::Identifier, "expected name after '::'")?.value.get_str());
+                }
+
+                if self.stream.check_current(TokenKind::LeftBrace) && !self.no_struct_literal {
+                    return self.parse_struct_literal(path, span);
+                }
+
+                if path.segments.len() > 1 {
+                    return Ok(Expr::new(ExprKind::Path(path), span));
+                }
+
+                return Ok(Expr::new(ExprKind::Ident(path.segments.remove(0)), span));
+            }
+            (TokenKind::Pipe, _) => return self.parse_closure(),
+            (TokenKind::LeftBracket, _) => return self.parse_array(),
+            (TokenKind::LeftParen, _) => return self.parse_parens(),
+            (TokenKind::Loop, _) => {
+                let loop_stmt = StmtParser::new(self.stream).parse_loop()?;
+                return Ok(Expr::new(ExprKind::Loop(Box::new(loop_stmt)), span));
+            }
+            _ => return Err(self.stream.error_at_current("expected expression")),
+        };
+
+        self.stream.next();
+
+        Ok(Expr::new(kind, span))
     }
 
-    fn parse_factor(&mut self) -> Option<Expr> {
-        None
+    /// Parses a call argument, `value`, `name: value` or `...values`.
+    fn parse_arg(&mut self) -> ParseResult<Arg> {
+        let span = self.stream.current().span();
+        let is_named = self.stream.check_current(TokenKind::Identifier) && self.stream.peek().is_some_and(|token| token.kind == TokenKind::Colon);
+
+        let name = if is_named {
+            let name = self.stream.next().unwrap().value.get_str();
+            self.stream.next();
+            Some(name)
+        } else {
+            None
+        };
+
+        let spread = name.is_none() && self.stream.advance_if_match(TokenKind::DotDotDot);
+        let value = self.parse_nested()?;
+
+        Ok(Arg { name, value, spread, span })
     }
 
-    fn parse_unary(&mut self) -> Option<Expr> {
-        None
+    /// Parses a parenthesized expression, or a tuple `(a, b, ...)` if there's a comma.
+    fn parse_parens(&mut self) -> ParseResult<Expr> {
+        let span = self.stream.next_expected(TokenKind::LeftParen, "expected '('")?.span();
+        let expr = self.parse_nested()?;
+
+        if !self.stream.check_current(TokenKind::Comma) {
+            self.stream.next_expected(TokenKind::RightParen, "expected ')' after expression")?;
+            return Ok(expr);
+        }
+
+        let mut elems = vec![expr];
+
+        while self.stream.advance_if_match(TokenKind::Comma) && !self.stream.check_current(TokenKind::RightParen) {
+            elems.push(self.parse_nested()?);
+        }
+
+        self.stream.next_expected(TokenKind::RightParen, "expected ')' after tuple elements")?;
+
+        if elems.len() < 2 {
+            return Err(self.stream.error("a tuple needs at least two elements", span));
+        }
+
+        Ok(Expr::new(ExprKind::Tuple(elems), span))
     }
 
-    fn parse_base(&mut self) -> Option<Expr> {
-        None
+    /// Parses the name of a field, or the index of a tuple element.
+    fn parse_field_name(&mut self, after: &str) -> ParseResult<String> {
+        if let (TokenKind::Int, Value::Int(index)) = (self.stream.current().kind, &self.stream.current().value) {
+            let index = index.to_string();
+            self.stream.next();
+            return Ok(index);
+        }
+
+        Ok(self.stream.next_expected(TokenKind::Identifier, &format!("expected field name after {after}"))?.value.get_str())
     }
 
-    fn parse_operand(&mut self) -> Option<Expr> {
-        None
+    fn previous_binary_op(&self) -> (BinaryOp, Span) {
+        let token = self.stream.previous();
+
+        let op = match token.kind {
+            TokenKind::Plus => BinaryOp::Add,
+            TokenKind::Minus => BinaryOp::Sub,
+            TokenKind::Star => BinaryOp::Mul,
+            TokenKind::Slash => BinaryOp::Div,
+            TokenKind::EqualEqual => BinaryOp::Eq,
+            TokenKind::BangEqual => BinaryOp::NotEq,
+            TokenKind::Less => BinaryOp::Less,
+            TokenKind::LessEqual => BinaryOp::LessEq,
+            TokenKind::Greater => BinaryOp::Greater,
+            TokenKind::GreaterEqual => BinaryOp::GreaterEq,
+            kind => unreachable!("{kind:?} is not a binary operator"),
+        };
+
+        (op, token.span())
     }
 }
@@ -1,3 +1,11 @@
 pub mod decl_parser;
 mod expr_parser;
-mod stmt_parser;
\ No newline at end of file
+mod pattern_parser;
+mod stmt_parser;
+mod type_parser;
+
+/// Marker for a failed parse. The diagnostic describing it is already recorded in the `TokenStream`.
+#[derive(Debug)]
+pub struct ParseError;
+
+pub type ParseResult<T> = Result<T, ParseError>;
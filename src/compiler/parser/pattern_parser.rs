@@ -0,0 +1,133 @@
+use crate::compiler::ast::expr::Path;
+use crate::compiler::ast::pattern::{FieldPattern, Pattern, PatternKind};
+use crate::compiler::token::{TokenKind, Value};
+use crate::compiler::token_stream::TokenStream;
+
+use super::expr_parser::ExprParser;
+use super::ParseResult;
+
+pub struct PatternParser<'a> {
+    stream: &'a mut TokenStream,
+}
+
+impl<'a> PatternParser<'a> {
+    pub fn new(stream: &'a mut TokenStream) -> Self {
+        PatternParser { stream }
+    }
+
+    /// A pattern starting with a name is a wildcard, a binding, a variant or a struct. Anything else, including a name
+    /// followed by an operator, is a constant expression or a range.
+    pub fn parse(&mut self) -> ParseResult<Pattern> {
+        let current = self.stream.current();
+        let span = current.span();
+
+        if current.kind == TokenKind::Identifier {
+            if current.value == Value::from("_") {
+                self.stream.next();
+                return Ok(Pattern { kind: PatternKind::Wildcard, span });
+            }
+
+            let next = self.stream.peek().map(|token| token.kind);
+
+            if matches!(next, Some(TokenKind::ColonColon | TokenKind::LeftBrace)) {
+                return self.parse_path_pattern();
+            }
+
+            if matches!(
+                next,
+                Some(TokenKind::Comma | TokenKind::Pipe | TokenKind::MinusGreater | TokenKind::If | TokenKind::RightParen | TokenKind::RightBrace)
+            ) {
+                let name = self.stream.next().unwrap().value.get_str();
+                return Ok(Pattern { kind: PatternKind::Ident(name), span });
+            }
+        }
+
+        let expr = ExprParser::new(self.stream).no_struct_literal().parse()?;
+
+        if matches!(self.stream.current().kind, TokenKind::DotDot | TokenKind::DotDotEqual) {
+            let range = ExprParser::new(self.stream).no_struct_literal().parse_range(expr)?;
+            return Ok(Pattern {
+                kind: PatternKind::Range(range),
+                span,
+            });
+        }
+
+        Ok(Pattern { kind: PatternKind::Expr(expr), span })
+    }
+
+    /// Parses `Path`, `Path(patterns, ...)` or `Path { fields, ... }`.
+    fn parse_path_pattern(&mut self) -> ParseResult<Pattern> {
+        let span = self.stream.current().span();
+        let mut path = Path {
+            segments: vec![self.stream.next().unwrap().value.get_str()],
+        };
+
+        while self.stream.advance_if_match(TokenKind::ColonColon) {
+            path.segments.push(self.stream.next_expected(TokenKind::Identifier, "expected name after '::'")?.value.get_str());
+        }
+
+        if self.stream.advance_if_match(TokenKind::LeftParen) {
+            let mut patterns = Vec::<Pattern>::new();
+
+            while !self.stream.check_current(TokenKind::RightParen) {
+                patterns.push(self.parse()?);
+
+                if !self.stream.advance_if_match(TokenKind::Comma) {
+                    break;
+                }
+            }
+
+            self.stream.next_expected(TokenKind::RightParen, "expected ')' after patterns")?;
+
+            return Ok(Pattern {
+                kind: PatternKind::Variant(path, Some(patterns)),
+                span,
+            });
+        }
+
+        if !self.stream.advance_if_match(TokenKind::LeftBrace) {
+            return Ok(Pattern {
+                kind: PatternKind::Variant(path, None),
+                span,
+            });
+        }
+
+        let mut fields = Vec::<FieldPattern>::new();
+        let mut has_rest = false;
+
+        while !self.stream.check_current(TokenKind::RightBrace) {
+            if self.stream.advance_if_match(TokenKind::DotDot) {
+                has_rest = true;
+                break;
+            }
+
+            let name = self.stream.next_expected(TokenKind::Identifier, "expected field name or '..'")?.clone();
+
+            let pattern = if self.stream.advance_if_match(TokenKind::Colon) {
+                self.parse()?
+            } else {
+                Pattern {
+                    kind: PatternKind::Ident(name.value.get_str()),
+                    span: name.span(),
+                }
+            };
+
+            fields.push(FieldPattern {
+                name: name.value.get_str(),
+                pattern,
+                span: name.span(),
+            });
+
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        self.stream.next_expected(TokenKind::RightBrace, "expected '}' after field patterns")?;
+
+        Ok(Pattern {
+            kind: PatternKind::Struct(path, fields, has_rest),
+            span,
+        })
+    }
+}
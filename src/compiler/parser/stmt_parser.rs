@@ -1,7 +1,12 @@
-use crate::compiler::ast::stmt::Stmt;
+use crate::compiler::ast::pattern::Pattern;
+use crate::compiler::ast::stmt::{AttributedStmt, Block, BreakStmt, BreakTarget, ContinueStmt, DeferStmt, ForIter, ForStmt, IfStmt, LoopStmt, ReturnStmt, Stmt, SwitchArm, SwitchStmt, WhileStmt};
 use crate::compiler::token::{TokenKind, Value};
 use crate::compiler::token_stream::TokenStream;
-use std::vec;
+
+use super::decl_parser::DeclParser;
+use super::expr_parser::ExprParser;
+use super::pattern_parser::PatternParser;
+use super::ParseResult;
 
 pub struct StmtParser<'a> {
     stream: &'a mut TokenStream,
@@ -9,12 +14,332 @@ pub struct StmtParser<'a> {
 
 impl<'a> StmtParser<'a> {
     pub fn new(stream: &'a mut TokenStream) -> Self {
-        StmtParser { stream: stream }
+        StmtParser { stream }
+    }
+
+    pub fn parse(&mut self) -> ParseResult<Vec<Stmt>> {
+        Ok(self.parse_block()?.stmts)
+    }
+
+    /// Parses a block, replacing malformed statements with `Stmt::Error`. Fails only when the block runs into the next
+    /// top-level declaration or the end of file, since the block can't be closed then.
+    pub fn parse_block(&mut self) -> ParseResult<Block> {
+        let span = self.stream.next_expected(TokenKind::LeftBrace, "expected '{' at the start of block")?.span();
+        let mut stmts = Vec::<Stmt>::new();
+
+        while !self.stream.check_current(TokenKind::RightBrace) && !self.stream.is_at_end() {
+            if self.stream.current().kind.starts_decl() && !self.starts_stmt() {
+                break;
+            }
+
+            let start = self.stream.position();
+            match self.parse_stmt() {
+                Ok(stmt) => stmts.push(stmt),
+                Err(_) => {
+                    stmts.push(Stmt::Error);
+                    self.synchronize(start);
+                }
+            }
+        }
+
+        self.stream.next_expected(TokenKind::RightBrace, "expected '}' at the end of block")?;
+
+        Ok(Block { stmts, span })
+    }
+
+    fn parse_stmt(&mut self) -> ParseResult<Stmt> {
+        match self.stream.current().kind {
+            TokenKind::Var => Ok(Stmt::Var(DeclParser::new(self.stream).parse_var()?)),
+            TokenKind::Let => Ok(Stmt::Let(DeclParser::new(self.stream).parse_let()?)),
+            TokenKind::Return => self.parse_return(),
+            TokenKind::If => Ok(Stmt::If(self.parse_if()?)),
+            TokenKind::While => Ok(Stmt::While(self.parse_while()?)),
+            TokenKind::Loop => Ok(Stmt::Loop(self.parse_loop()?)),
+            TokenKind::For => Ok(Stmt::For(self.parse_for()?)),
+            TokenKind::Identifier if self.stream.peek().is_some_and(|token| token.kind == TokenKind::Colon) => self.parse_labeled(),
+            TokenKind::Switch => self.parse_switch(),
+            TokenKind::Fall => {
+                let span = self.stream.next().unwrap().span();
+                self.expect_semicolon()?;
+                Ok(Stmt::Fall(span))
+            }
+            TokenKind::Break => self.parse_break(),
+            TokenKind::Continue => {
+                let span = self.stream.next().unwrap().span();
+                let label = self.parse_label()?;
+                self.expect_semicolon()?;
+                Ok(Stmt::Continue(ContinueStmt { label, span }))
+            }
+            TokenKind::Defer => {
+                let span = self.stream.next().unwrap().span();
+                let stmt = Box::new(self.parse_stmt()?);
+                Ok(Stmt::Defer(DeferStmt { stmt, span }))
+            }
+            TokenKind::Print => {
+                self.stream.next();
+                let expr = ExprParser::new(self.stream).parse()?;
+                self.expect_semicolon()?;
+                Ok(Stmt::Print(expr))
+            }
+            TokenKind::LeftBrace => Ok(Stmt::Block(self.parse_block()?)),
+            TokenKind::Sharp => {
+                let attrs = DeclParser::new(self.stream).parse_attributes()?;
+                let stmt = Box::new(self.parse_stmt()?);
+                Ok(Stmt::Attributed(AttributedStmt { attrs, stmt }))
+            }
+            _ => {
+                let expr = ExprParser::new(self.stream).parse()?;
+                self.expect_semicolon()?;
+                Ok(Stmt::Expr(expr))
+            }
+        }
+    }
+
+    fn parse_return(&mut self) -> ParseResult<Stmt> {
+        let span = self.stream.next_expected(TokenKind::Return, "expected 'return'")?.span();
+
+        let expr = if self.stream.check_current(TokenKind::Semicolon) {
+            None
+        } else {
+            Some(ExprParser::new(self.stream).parse()?)
+        };
+
+        self.expect_semicolon()?;
+
+        Ok(Stmt::Return(ReturnStmt { expr, span }))
+    }
+
+    fn parse_if(&mut self) -> ParseResult<IfStmt> {
+        let span = self.stream.next_expected(TokenKind::If, "expected 'if'")?.span();
+
+        let binding = if self.stream.advance_if_match(TokenKind::Let) {
+            let name = self.stream.next_expected(TokenKind::Identifier, "expected name after 'if let'")?.value.get_str();
+            self.stream.next_expected(TokenKind::Equal, "expected '=' after 'if let' name")?;
+            Some(name)
+        } else {
+            None
+        };
+
+        let cond = ExprParser::new(self.stream).no_struct_literal().parse()?;
+        let then_block = self.parse_block()?;
+
+        let else_branch = if self.stream.advance_if_match(TokenKind::Else) {
+            if self.stream.check_current(TokenKind::If) {
+                Some(Box::new(Stmt::If(self.parse_if()?)))
+            } else {
+                Some(Box::new(Stmt::Block(self.parse_block()?)))
+            }
+        } else {
+            None
+        };
+
+        Ok(IfStmt {
+            binding,
+            cond,
+            then_block,
+            else_branch,
+            span,
+        })
+    }
+
+    fn parse_while(&mut self) -> ParseResult<WhileStmt> {
+        let span = self.stream.next_expected(TokenKind::While, "expected 'while'")?.span();
+        let cond = ExprParser::new(self.stream).no_struct_literal().parse()?;
+        let body = self.parse_block()?;
+
+        Ok(WhileStmt { label: None, cond, body, span })
+    }
+
+    pub fn parse_loop(&mut self) -> ParseResult<LoopStmt> {
+        let span = self.stream.next_expected(TokenKind::Loop, "expected 'loop'")?.span();
+        let body = self.parse_block()?;
+
+        Ok(LoopStmt { label: None, body, span })
+    }
+
+    /// Parses `label: loop { ... }`, or a labeled `while` or `for`.
+    fn parse_labeled(&mut self) -> ParseResult<Stmt> {
+        let label = Some(self.stream.next().unwrap().value.get_str());
+        self.stream.next_expected(TokenKind::Colon, "expected ':' after label")?;
+
+        match self.stream.current().kind {
+            TokenKind::While => Ok(Stmt::While(WhileStmt { label, ..self.parse_while()? })),
+            TokenKind::Loop => Ok(Stmt::Loop(LoopStmt { label, ..self.parse_loop()? })),
+            TokenKind::For => Ok(Stmt::For(ForStmt { label, ..self.parse_for()? })),
+            _ => Err(self.stream.error_at_current("expected 'loop', 'while' or 'for' after label")),
+        }
+    }
+
+    /// Parses `break;`, `break name;` or `break value;`.
+    fn parse_break(&mut self) -> ParseResult<Stmt> {
+        let span = self.stream.next_expected(TokenKind::Break, "expected 'break'")?.span();
+
+        let target = if self.stream.check_current(TokenKind::Semicolon) {
+            BreakTarget::None
+        } else if self.is_label() {
+            let token = self.stream.next().unwrap();
+            BreakTarget::Name(token.value.get_str(), token.span())
+        } else {
+            BreakTarget::Value(ExprParser::new(self.stream).parse()?)
+        };
+
+        self.expect_semicolon()?;
+
+        Ok(Stmt::Break(BreakStmt { target, span }))
     }
 
-    pub fn parse(&mut self) -> Vec<Stmt> {
-        self.stream.next_expected(TokenKind::LeftBrace, "expceted '{' after function parameters");
-        self.stream.next_expected(TokenKind::RightBrace, "expceted '}' after function body");
-        vec![]
+    /// Parses the label after `continue`, a name right before the `;`.
+    fn parse_label(&mut self) -> ParseResult<Option<String>> {
+        Ok(self.is_label().then(|| self.stream.next().unwrap().value.get_str()))
+    }
+
+    fn is_label(&self) -> bool {
+        self.stream.check_current(TokenKind::Identifier) && self.stream.peek().is_some_and(|token| token.kind == TokenKind::Semicolon)
+    }
+
+    /// Parses `for [var] name in start..end [step expr] { ... }` or `for [var] name in collection { ... }`.
+    fn parse_for(&mut self) -> ParseResult<ForStmt> {
+        let span = self.stream.next_expected(TokenKind::For, "expected 'for'")?.span();
+        let is_mutable = self.stream.advance_if_match(TokenKind::Var);
+        let name = self.stream.next_expected(TokenKind::Identifier, "expected loop variable name")?.value.get_str();
+
+        self.stream.next_expected(TokenKind::In, "expected 'in' after loop variable")?;
+
+        let expr = ExprParser::new(self.stream).no_struct_literal().parse()?;
+
+        let iter = if matches!(self.stream.current().kind, TokenKind::DotDot | TokenKind::DotDotEqual) {
+            let range = ExprParser::new(self.stream).no_struct_literal().parse_range(expr)?;
+
+            let step = if self.stream.current().kind == TokenKind::Identifier && self.stream.current().value == Value::from("step") {
+                self.stream.next();
+                Some(ExprParser::new(self.stream).no_struct_literal().parse()?)
+            } else {
+                None
+            };
+
+            ForIter::Range { range, step }
+        } else {
+            ForIter::Collection(expr)
+        };
+
+        let body = self.parse_block()?;
+
+        Ok(ForStmt {
+            label: None,
+            name,
+            is_mutable,
+            iter,
+            body,
+            span,
+        })
+    }
+
+    fn parse_switch(&mut self) -> ParseResult<Stmt> {
+        let span = self.stream.next_expected(TokenKind::Switch, "expected 'switch'")?.span();
+        let subject = ExprParser::new(self.stream).no_struct_literal().parse()?;
+        let mut arms = Vec::<SwitchArm>::new();
+
+        self.stream.next_expected(TokenKind::LeftBrace, "expected '{' after switch subject")?;
+
+        while !self.stream.check_current(TokenKind::RightBrace) && !self.stream.is_at_end() {
+            arms.push(self.parse_switch_arm()?);
+        }
+
+        self.stream.next_expected(TokenKind::RightBrace, "expected '}' at the end of switch")?;
+
+        Ok(Stmt::Switch(SwitchStmt { subject, arms, span }))
+    }
+
+    /// Parses `cases -> body` where the body is either a block or a single statement.
+    fn parse_switch_arm(&mut self) -> ParseResult<SwitchArm> {
+        let span = self.stream.current().span();
+        let is_else = self.stream.advance_if_match(TokenKind::Else);
+        let mut cases = Vec::<Pattern>::new();
+
+        if !is_else {
+            loop {
+                cases.push(PatternParser::new(self.stream).parse()?);
+
+                if !self.stream.advance_if_match_any(&[TokenKind::Comma, TokenKind::Pipe]) {
+                    break;
+                }
+            }
+        }
+
+        let guard = if !is_else && self.stream.advance_if_match(TokenKind::If) {
+            Some(ExprParser::new(self.stream).parse()?)
+        } else {
+            None
+        };
+
+        self.stream.next_expected(TokenKind::MinusGreater, "expected '->' after switch cases")?;
+
+        let body = if self.stream.check_current(TokenKind::LeftBrace) {
+            self.parse_block()?
+        } else {
+            let body_span = self.stream.current().span();
+
+            Block {
+                stmts: vec![self.parse_stmt()?],
+                span: body_span,
+            }
+        };
+
+        Ok(SwitchArm { cases, guard, is_else, body, span })
+    }
+
+    fn expect_semicolon(&mut self) -> ParseResult<()> {
+        self.stream.next_expected(TokenKind::Semicolon, "expected ';' at the end of statement")?;
+        Ok(())
+    }
+
+    fn starts_stmt(&self) -> bool {
+        matches!(
+            self.stream.current().kind,
+            TokenKind::Var
+                | TokenKind::Let
+                | TokenKind::Return
+                | TokenKind::If
+                | TokenKind::While
+                | TokenKind::Loop
+                | TokenKind::For
+                | TokenKind::Switch
+                | TokenKind::Fall
+                | TokenKind::Break
+                | TokenKind::Continue
+                | TokenKind::Defer
+                | TokenKind::Print
+        )
+    }
+
+    /// Skips tokens up to the end of the malformed statement: past the next `;`, or up to the `}` closing the block or
+    /// the next statement or declaration keyword. Nested blocks are skipped as a whole, including a block the statement
+    /// had already opened when it failed, so an error inside a switch arm doesn't leave the rest of the switch behind.
+    fn synchronize(&mut self, start: usize) {
+        let opened = self.stream.depth_since(start);
+        let mut depth = opened;
+
+        while !self.stream.is_at_end() {
+            let kind = self.stream.current().kind;
+            let at_boundary = depth == 0 && self.stream.position() > start;
+
+            match kind {
+                TokenKind::Semicolon if depth == 0 => {
+                    self.stream.next();
+                    return;
+                }
+                TokenKind::RightBrace if depth == 0 => return,
+                TokenKind::RightBrace if depth == 1 && opened > 0 => {
+                    self.stream.next();
+                    return;
+                }
+                TokenKind::RightBrace => depth -= 1,
+                TokenKind::LeftBrace => depth += 1,
+                _ if at_boundary && (kind.starts_decl() || self.starts_stmt()) => return,
+                _ => {}
+            }
+
+            self.stream.next();
+        }
     }
 }
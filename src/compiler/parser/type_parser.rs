@@ -0,0 +1,155 @@
+use crate::compiler::ast::expr::Path;
+use crate::compiler::ast::type_info::{TypeInfo, TypeKind};
+use crate::compiler::token::TokenKind;
+use crate::compiler::token_stream::TokenStream;
+
+use super::expr_parser::ExprParser;
+use super::ParseResult;
+
+pub struct TypeParser<'a> {
+    stream: &'a mut TokenStream,
+}
+
+impl<'a> TypeParser<'a> {
+    pub fn new(stream: &'a mut TokenStream) -> Self {
+        TypeParser { stream }
+    }
+
+    pub fn parse(&mut self) -> ParseResult<TypeInfo> {
+        if self.stream.check_current(TokenKind::LeftBracket) {
+            return self.parse_array();
+        }
+
+        if self.stream.check_current(TokenKind::Fn) {
+            return self.parse_fn();
+        }
+
+        if self.stream.check_current(TokenKind::LeftParen) {
+            return self.parse_tuple();
+        }
+
+        if self.stream.check_current(TokenKind::Hat) {
+            let span = self.stream.next().unwrap().span();
+            let pointee = self.parse()?;
+
+            return Ok(TypeInfo {
+                kind: TypeKind::Pointer(Box::new(pointee)),
+                span,
+            });
+        }
+
+        if self.stream.check_current(TokenKind::Question) {
+            let span = self.stream.next().unwrap().span();
+            let inner = self.parse()?;
+
+            return Ok(TypeInfo {
+                kind: TypeKind::Optional(Box::new(inner)),
+                span,
+            });
+        }
+
+        let name = self.stream.next_expected(TokenKind::Identifier, "expected type name")?.clone();
+        let mut path = Path { segments: vec![name.value.get_str()] };
+
+        while self.stream.advance_if_match(TokenKind::ColonColon) {
+            path.segments.push(self.stream.next_expected(TokenKind::Identifier, "expected type name after '::'")?.value.get_str());
+        }
+
+        let kind = if self.stream.check_current(TokenKind::Less) {
+            TypeKind::Generic(path, self.parse_args()?)
+        } else if path.segments.len() > 1 {
+            TypeKind::Path(path)
+        } else {
+            TypeKind::Base(path.segments.remove(0))
+        };
+
+        Ok(TypeInfo { kind, span: name.span() })
+    }
+
+    /// Parses type arguments `<T, ...>`.
+    pub fn parse_args(&mut self) -> ParseResult<Vec<TypeInfo>> {
+        self.stream.next_expected(TokenKind::Less, "expected '<'")?;
+
+        let mut args = Vec::<TypeInfo>::new();
+
+        while !self.stream.check_current(TokenKind::Greater) {
+            args.push(self.parse()?);
+
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        self.stream.next_expected(TokenKind::Greater, "expected '>' after type arguments")?;
+
+        Ok(args)
+    }
+
+    /// Parses `fn(T, ...) [-> R]`.
+    fn parse_fn(&mut self) -> ParseResult<TypeInfo> {
+        let span = self.stream.next_expected(TokenKind::Fn, "expected 'fn'")?.span();
+
+        self.stream.next_expected(TokenKind::LeftParen, "expected '(' after 'fn'")?;
+
+        let mut params = Vec::<TypeInfo>::new();
+
+        while !self.stream.check_current(TokenKind::RightParen) {
+            params.push(self.parse()?);
+
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        self.stream.next_expected(TokenKind::RightParen, "expected ')' after parameter types")?;
+
+        let return_type = if self.stream.advance_if_match(TokenKind::MinusGreater) {
+            Some(Box::new(self.parse()?))
+        } else {
+            None
+        };
+
+        Ok(TypeInfo {
+            kind: TypeKind::Fn(params, return_type),
+            span,
+        })
+    }
+
+    /// Parses `(T, U, ...)`.
+    fn parse_tuple(&mut self) -> ParseResult<TypeInfo> {
+        let span = self.stream.next_expected(TokenKind::LeftParen, "expected '('")?.span();
+        let mut elems = Vec::<TypeInfo>::new();
+
+        while !self.stream.check_current(TokenKind::RightParen) {
+            elems.push(self.parse()?);
+
+            if !self.stream.advance_if_match(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        self.stream.next_expected(TokenKind::RightParen, "expected ')' after tuple element types")?;
+
+        if elems.len() < 2 {
+            return Err(self.stream.error("a tuple type needs at least two elements", span));
+        }
+
+        Ok(TypeInfo { kind: TypeKind::Tuple(elems), span })
+    }
+
+    /// Parses `[T; N]` or `[T]`.
+    fn parse_array(&mut self) -> ParseResult<TypeInfo> {
+        let span = self.stream.next_expected(TokenKind::LeftBracket, "expected '['")?.span();
+        let elem = Box::new(self.parse()?);
+
+        let kind = if self.stream.advance_if_match(TokenKind::Semicolon) {
+            TypeKind::Array(elem, Box::new(ExprParser::new(self.stream).parse()?))
+        } else {
+            TypeKind::Slice(elem)
+        };
+
+        self.stream.next_expected(TokenKind::RightBracket, "expected ']' after array type")?;
+
+        Ok(TypeInfo { kind, span })
+    }
+}
@@ -1,3 +1,4 @@
+use crate::compiler::diagnostic::Span;
 use crate::utils::exit_with_err_msg;
 
 #[derive(PartialEq, Debug, Clone, Copy)]
@@ -16,6 +17,8 @@ pub enum TokenKind {
     Slash,
     Dot,
     DotDot,
+    DotDotEqual,
+    DotDotDot,
     Sharp,
     Comma,
     Semicolon,
@@ -37,6 +40,8 @@ pub enum TokenKind {
     StarEqual,
     PlusEqual,
     Question,
+    QuestionDot,
+    QuestionQuestion,
     False,
     True,
     Fn,
@@ -45,6 +50,7 @@ pub enum TokenKind {
     Else,
     Print,
     For,
+    In,
     While,
     Struct,
     Internal,
@@ -53,6 +59,7 @@ pub enum TokenKind {
     Or,
     Let,
     Var,
+    Const,
     Nil,
     Return,
     Break,
@@ -68,9 +75,21 @@ pub enum TokenKind {
     Fall,
     Import,
     Defer,
+    Interface,
     Eof,
 }
 
+impl TokenKind {
+    /// Whether the token starts a top-level declaration, which makes it a synchronization point for error recovery.
+    pub fn starts_decl(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Var | TokenKind::Let | TokenKind::Const | TokenKind::Fn | TokenKind::Struct | TokenKind::Enum | TokenKind::Impl | TokenKind::Interface | TokenKind::Import | TokenKind::Internal
+        )
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, PartialEq, Debug)]
 pub enum TokenSuffix {
     None,
@@ -83,8 +102,8 @@ pub enum TokenSuffix {
 }
 
 impl TokenSuffix {
-    pub fn from(str: &String) -> Option<TokenSuffix> {
-        match str.as_str() {
+    pub fn from(str: &str) -> Option<TokenSuffix> {
+        match str {
             "D" => Some(TokenSuffix::D),
             "L" => Some(TokenSuffix::L),
             "LL" => Some(TokenSuffix::LL),
@@ -96,22 +115,6 @@ impl TokenSuffix {
     }
 }
 
-#[derive(Clone, Debug)]
-pub enum TokenMod {
-    None,
-    Hex,
-    Bin,
-    Oct,
-    Char,
-    Multiline,
-}
-
-pub enum TypeKind {
-    Base,
-    Pointer,
-    Array,
-}
-
 #[derive(Clone, PartialEq, Debug)]
 pub enum Value {
     None,
@@ -136,40 +139,56 @@ impl Value {
 #[derive(Clone, Debug)]
 pub struct Token {
     pub kind: TokenKind,
-    pub modd: TokenMod,
     pub suffix: TokenSuffix,
     pub value: Value,
     pub line: u32,
+    pub column: u32,
 }
 
 impl Token {
     pub fn new(kind: TokenKind, line: u32, value: Value) -> Self {
         Token {
-            kind: kind,
-            modd: TokenMod::None,
+            kind,
             suffix: TokenSuffix::None,
-            value: value,
-            line: line,
+            value,
+            line,
+            column: 0,
         }
     }
 
     pub fn new_number(kind: TokenKind, line: u32, value: Value, suffix: TokenSuffix) -> Self {
-        Token {
-            kind: kind,
-            modd: TokenMod::None,
-            suffix: suffix,
-            value: value,
-            line: line,
-        }
+        Token { kind, suffix, value, line, column: 0 }
     }
 
     pub fn eof() -> Self {
         Token {
             kind: TokenKind::Eof,
             value: Value::None,
-            modd: TokenMod::None,
             suffix: TokenSuffix::None,
             line: 0,
+            column: 0,
+        }
+    }
+
+    pub fn at(mut self, line: u32, column: u32) -> Self {
+        self.line = line;
+        self.column = column;
+        self
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.line, self.column)
+    }
+
+    /// Describes the token for diagnostics, e.g. `'}'` or `end of file`.
+    pub fn describe(&self) -> String {
+        match (self.kind, &self.value) {
+            (TokenKind::Eof, _) => "end of file".to_string(),
+            (TokenKind::String, Value::Str(s)) => format!("'\"{s}\"'"),
+            (_, Value::Str(s)) => format!("'{s}'"),
+            (_, Value::Int(i)) => format!("'{i}'"),
+            (_, Value::Float(f)) => format!("'{f}'"),
+            (kind, Value::None) => format!("{kind:?}"),
         }
     }
 }
@@ -1,22 +1,34 @@
+use crate::compiler::diagnostic::{Diagnostic, Span};
+use crate::compiler::parser::ParseError;
 use crate::compiler::token::{Token, TokenKind};
 
 pub struct TokenStream {
     current: usize,
     tokens: Vec<Token>,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl TokenStream {
-    pub fn new(tokens: Vec<Token>) -> Self {
+    pub fn new(mut tokens: Vec<Token>) -> Self {
+        let eof = match tokens.last() {
+            Some(last) => Token::eof().at(last.line, last.column + 1),
+            None => Token::eof(),
+        };
+
+        tokens.push(eof);
+
         TokenStream {
             current: 0,
-            tokens: tokens,
+            tokens,
+            diagnostics: Vec::new(),
         }
     }
 
     pub fn is_at_end(&self) -> bool {
-        self.current == self.tokens.len() || matches!(self.current().kind, TokenKind::Eof)
+        matches!(self.current().kind, TokenKind::Eof)
     }
 
+    /// Returns the token that has not been consumed yet.
     pub fn current(&self) -> &Token {
         &self.tokens[self.current]
     }
@@ -25,19 +37,72 @@ impl TokenStream {
         self.tokens.get(self.current + 1)
     }
 
+    pub fn previous(&self) -> &Token {
+        &self.tokens[self.current - 1]
+    }
+
+    /// Consumes the current token and returns it. The trailing `Eof` token is never consumed.
     pub fn next(&mut self) -> Option<&Token> {
+        if self.is_at_end() {
+            return None;
+        }
+
         self.current += 1;
-        self.tokens.get(self.current)
+        self.tokens.get(self.current - 1)
     }
 
-    pub fn next_expected(&mut self, kind: TokenKind, msg: &str) -> &Token {
-        self.next().filter(|token| token.kind == kind).expect(msg)
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    /// Number of braces opened and not yet closed by the tokens consumed since `start`.
+    pub fn depth_since(&self, start: usize) -> usize {
+        self.tokens[start..self.current].iter().fold(0, |depth, token| match token.kind {
+            TokenKind::LeftBrace => depth + 1,
+            TokenKind::RightBrace => depth.saturating_sub(1),
+            _ => depth,
+        })
+    }
+
+    pub fn next_expected(&mut self, kind: TokenKind, msg: &str) -> Result<&Token, ParseError> {
+        if !self.check_current(kind) {
+            return Err(self.error_at_current(msg));
+        }
+
+        Ok(self.next().unwrap())
+    }
+
+    /// Records a diagnostic pointing at the current token and mentioning what was found there.
+    pub fn error_at_current(&mut self, msg: &str) -> ParseError {
+        let token = self.current();
+        let message = format!("{msg}, found {}", token.describe());
+        let span = token.span();
+
+        self.error(message, span)
+    }
+
+    /// Records a diagnostic unless one was already reported at the same position, which happens when an error
+    /// propagates through several enclosing constructs that all expected something else at that token.
+    pub fn error(&mut self, message: impl Into<String>, span: Span) -> ParseError {
+        if self.diagnostics.last().is_none_or(|last| last.span != span) {
+            self.diagnostics.push(Diagnostic::error(message, span));
+        }
+
+        ParseError
+    }
+
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
     }
 
     pub fn check(&self, token: &Token, kind: TokenKind) -> bool {
         token.kind == kind
     }
 
+    pub fn check_current(&self, kind: TokenKind) -> bool {
+        self.check(self.current(), kind)
+    }
+
     pub fn advance_if_match(&mut self, kind: TokenKind) -> bool {
         if self.current().kind == kind {
             self.next();
@@ -47,17 +112,17 @@ impl TokenStream {
         }
     }
 
-    fn advance_if_match_any(&mut self, kinds: &[TokenKind]) -> bool {
+    pub fn advance_if_match_any(&mut self, kinds: &[TokenKind]) -> bool {
         for kind in kinds {
             if self.advance_if_match(*kind) {
                 return true;
             }
         }
 
-        return false;
+        false
     }
 
-    fn advance_if_cmp(&mut self) -> bool {
+    pub fn advance_if_cmp(&mut self) -> bool {
         self.advance_if_match_any(&[
             TokenKind::Greater,
             TokenKind::GreaterEqual,
@@ -68,7 +133,7 @@ impl TokenStream {
         ])
     }
 
-    fn advance_if_unary(&mut self) -> bool {
+    pub fn advance_if_unary(&mut self) -> bool {
         self.advance_if_match_any(&[
             TokenKind::Minus,
             TokenKind::Bang,
@@ -78,7 +143,7 @@ impl TokenStream {
         ])
     }
 
-    fn is_assign(&mut self) -> bool {
+    pub fn is_assign(&mut self) -> bool {
         let assign_kinds = [
             TokenKind::Equal,
             TokenKind::PlusEqual,
@@ -93,6 +158,6 @@ impl TokenStream {
             }
         }
 
-        return false;
+        false
     }
 }